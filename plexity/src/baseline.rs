@@ -0,0 +1,310 @@
+/*  A module for recording the metrics of every file and function in a
+baseline, so that a codebase that is already over its limits can adopt
+them and only fail on code that is new or has got worse.
+
+The baseline is a JSON document holding a `schema_version` and a `files`
+object, keyed by path relative to the project root, where each file holds
+its `maximum_depth`, `average_depth`, `cyclomatic_complexity` and
+`cognitive_complexity` and a `functions` object with the same metrics keyed
+by qualified name. Keys are sorted so that the file changes as little as
+possible between snapshots.  */
+
+use crate::check::Violation;
+use crate::project::FileReport;
+use crate::report::Metrics;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{self, Component, Path, PathBuf};
+
+/// The version of the baseline layout.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// The name of the baseline file when none is given.
+pub const DEFAULT_FILE_NAME: &str = "plexity-baseline.json";
+
+/// A snapshot of the metrics that limits are checked against.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Baseline {
+    pub schema_version: u32,
+    pub files: BTreeMap<String, FileEntry>,
+}
+
+/// The recorded metrics of a file and of the functions in it.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FileEntry {
+    #[serde(flatten)]
+    pub metrics: Entry,
+    pub functions: BTreeMap<String, Entry>,
+}
+
+/// The recorded metrics of a file or function.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Entry {
+    pub maximum_depth: usize,
+    pub average_depth: f64,
+    pub cyclomatic_complexity: Option<usize>,
+    pub cognitive_complexity: Option<usize>,
+}
+
+impl Entry {
+    fn new(metrics: &Metrics) -> Entry {
+        Entry {
+            maximum_depth: metrics.maximum_depth,
+            average_depth: metrics.average_depth,
+            cyclomatic_complexity: metrics.cyclomatic_complexity,
+            cognitive_complexity: metrics.cognitive_complexity,
+        }
+    }
+
+    // The recorded value of a metric, by the name used in violations
+    fn value(&self, metric: &str) -> Option<f64> {
+        match metric {
+            "maximum_depth" => Some(self.maximum_depth as f64),
+            "average_depth" => Some(self.average_depth),
+            "cyclomatic_complexity" => self.cyclomatic_complexity.map(|value| value as f64),
+            "cognitive_complexity" => self.cognitive_complexity.map(|value| value as f64),
+            _ => None,
+        }
+    }
+
+    // Keeps the worse of two values for each metric, for functions that
+    // share a name (such as overloads)
+    fn merge(&mut self, other: Entry) {
+        self.maximum_depth = self.maximum_depth.max(other.maximum_depth);
+        self.average_depth = self.average_depth.max(other.average_depth);
+        self.cyclomatic_complexity = self.cyclomatic_complexity.max(other.cyclomatic_complexity);
+        self.cognitive_complexity = self.cognitive_complexity.max(other.cognitive_complexity);
+    }
+}
+
+impl Baseline {
+    /// Records the metrics of every file and function in `files`, under
+    /// their paths relative to `root`.
+    pub fn new(files: &[FileReport], root: &Path) -> Baseline {
+        let mut baseline = Baseline {
+            schema_version: SCHEMA_VERSION,
+            files: BTreeMap::new(),
+        };
+        for file in files {
+            let mut functions: BTreeMap<String, Entry> = BTreeMap::new();
+            for function in &file.report.functions {
+                let entry = Entry::new(&function.metrics);
+                match functions.get_mut(&function.name) {
+                    Some(existing) => existing.merge(entry),
+                    None => {
+                        functions.insert(function.name.clone(), entry);
+                    }
+                }
+            }
+            let entry = FileEntry {
+                metrics: Entry::new(&file.report.metrics),
+                functions,
+            };
+            baseline.files.insert(key(&file.path, root), entry);
+        }
+        baseline
+    }
+
+    /// Reads the baseline file at `path`.
+    pub fn load(path: &Path) -> io::Result<Baseline> {
+        let text = fs::read_to_string(path)?;
+        let baseline: Baseline = serde_json::from_str(&text).map_err(io::Error::from)?;
+        if baseline.schema_version != SCHEMA_VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "unsupported baseline schema version {}",
+                    baseline.schema_version
+                ),
+            ));
+        }
+        Ok(baseline)
+    }
+
+    /// Writes the baseline to `path` as pretty-printed JSON.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let mut text = serde_json::to_string_pretty(self).map_err(io::Error::from)?;
+        text.push('\n');
+        fs::write(path, text)
+    }
+
+    /// The number of functions recorded across every file.
+    pub fn function_count(&self) -> usize {
+        self.files.values().map(|file| file.functions.len()).sum()
+    }
+
+    /// Whether `violation` is new or worse than what was recorded: the
+    /// file or function is missing from the baseline, or its value for the
+    /// metric has gone up. Paths are matched relative to `root`, which
+    /// should be the root the baseline was recorded with.
+    pub fn is_regression(&self, violation: &Violation, root: &Path) -> bool {
+        let file = self.files.get(&key(&violation.path, root));
+        let entry = match &violation.function {
+            Some(name) => file.and_then(|file| file.functions.get(name)),
+            None => file.map(|file| &file.metrics),
+        };
+        entry
+            .and_then(|entry| entry.value(violation.metric))
+            .is_none_or(|recorded| violation.value > recorded)
+    }
+}
+
+// The key a file is recorded under: its path relative to `root`, with
+// forward slashes, so that `plexity baseline $PWD/src` and `plexity check
+// ./src` agree. Paths outside `root` are kept whole
+fn key(path: &str, root: &Path) -> String {
+    let path = clean(Path::new(path));
+    let root = clean(root);
+    let relative = path.strip_prefix(&root).unwrap_or(&path);
+    relative.to_string_lossy().replace('\\', "/")
+}
+
+// Makes `path` absolute and drops its `.` and `..` components, without
+// looking at the file system
+fn clean(path: &Path) -> PathBuf {
+    let absolute = path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+    let mut clean = PathBuf::new();
+    for component in absolute.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                clean.pop();
+            }
+            component => clean.push(component),
+        }
+    }
+    clean
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::check::{check, Limits, MetricLimits};
+    use crate::language::Language;
+    use crate::project::file_report;
+
+    fn limits() -> Limits {
+        Limits {
+            file: MetricLimits {
+                maximum_depth: Some(1),
+                ..Default::default()
+            },
+            function: MetricLimits {
+                cyclomatic_complexity: Some(1),
+                ..Default::default()
+            },
+        }
+    }
+
+    #[test]
+    fn test_only_new_or_worse_entities_fail() {
+        let legacy = "def f(a):\n    if a:\n        return 1\n";
+        let root = Path::new(".");
+        let baseline = Baseline::new(&[file_report("./src/a.py", legacy, Language::Python)], root);
+
+        // Unchanged code is within the baseline
+        let same = file_report("src/a.py", legacy, Language::Python);
+        assert!(check(&same, &limits())
+            .iter()
+            .all(|violation| !baseline.is_regression(violation, root)));
+
+        // A function that got more complex, and one that is new, both fail
+        let worse = "def f(a):\n    if a:\n        return 1\n    if a > 1:\n        return 2\n\ndef g(a):\n    if a:\n        return 2\n";
+        let regressions: Vec<Violation> =
+            check(&file_report("src/a.py", worse, Language::Python), &limits())
+                .into_iter()
+                .filter(|violation| baseline.is_regression(violation, root))
+                .collect();
+        let functions: Vec<Option<&str>> = regressions
+            .iter()
+            .map(|violation| violation.function.as_deref())
+            .collect();
+        assert!(functions.contains(&Some("f")));
+        assert!(functions.contains(&Some("g")));
+
+        // So does a file that is not in the baseline at all
+        let other = check(
+            &file_report("src/b.py", legacy, Language::Python),
+            &limits(),
+        );
+        assert!(other
+            .iter()
+            .all(|violation| baseline.is_regression(violation, root)));
+    }
+
+    #[test]
+    fn test_baseline_round_trip() {
+        let source = "def f():\n    pass\n";
+        let baseline = Baseline::new(
+            &[file_report("a.py", source, Language::Python)],
+            Path::new("."),
+        );
+        assert_eq!(baseline.function_count(), 1);
+        let path = std::env::temp_dir().join(format!(
+            "plexity-baseline-round-trip-{}.json",
+            std::process::id()
+        ));
+        baseline.save(&path).unwrap();
+        assert_eq!(Baseline::load(&path).unwrap(), baseline);
+
+        fs::write(&path, "{\"schema_version\": 2, \"files\": {}}").unwrap();
+        assert!(Baseline::load(&path).is_err());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_reloaded_baseline_has_no_regressions() {
+        // Average depths only match if they survive the trip through JSON exactly
+        let source = "def f(a):\n    x = a + 1\n    if a:\n        a = [a, (a,)]\n    if a:\n        a = [a, (a,)]\n";
+        let file = file_report("a.py", source, Language::Python);
+        let limits = Limits {
+            file: MetricLimits {
+                average_depth: Some(0.5),
+                ..Default::default()
+            },
+            function: MetricLimits {
+                average_depth: Some(0.5),
+                ..Default::default()
+            },
+        };
+        let root = Path::new(".");
+        let path = std::env::temp_dir().join(format!(
+            "plexity-baseline-reloaded-{}.json",
+            std::process::id()
+        ));
+        Baseline::new(std::slice::from_ref(&file), root)
+            .save(&path)
+            .unwrap();
+        let baseline = Baseline::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        let violations = check(&file, &limits);
+        assert!(!violations.is_empty());
+        assert!(violations
+            .iter()
+            .all(|violation| !baseline.is_regression(violation, root)));
+    }
+
+    #[test]
+    fn test_paths_are_relative_to_the_root() {
+        let directory = std::env::current_dir().unwrap();
+        let absolute = directory.join("src/a.py").to_string_lossy().into_owned();
+        assert_eq!(key(&absolute, Path::new(".")), "src/a.py");
+        assert_eq!(key("./src/../src/a.py", &directory), "src/a.py");
+        assert_eq!(key("src/a.py", Path::new("src")), "a.py");
+        assert_eq!(key("/elsewhere/a.py", &directory), "/elsewhere/a.py");
+
+        // A baseline recorded from absolute paths matches a relative check
+        let source = "def f(a):\n    if a:\n        return 1\n";
+        let root = Path::new(".");
+        let baseline = Baseline::new(&[file_report(&absolute, source, Language::Python)], root);
+        assert!(baseline.files.contains_key("src/a.py"));
+        assert!(check(
+            &file_report("src/a.py", source, Language::Python),
+            &limits()
+        )
+        .iter()
+        .all(|violation| !baseline.is_regression(violation, root)));
+    }
+}
@@ -0,0 +1,229 @@
+/*  A module for checking analysed files, and the functions in them,
+against configurable limits so that overly complex code can fail a build.  */
+
+use crate::project::FileReport;
+use crate::report::{Metrics, Position, Span};
+use serde::Serialize;
+
+/// The limits that each file (or each function) must stay within. A limit
+/// of `None` is not checked.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MetricLimits {
+    pub maximum_depth: Option<usize>,
+    pub average_depth: Option<f64>,
+    pub cyclomatic_complexity: Option<usize>,
+    pub cognitive_complexity: Option<usize>,
+}
+
+/// The limits for whole files and for the functions within them.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Limits {
+    pub file: MetricLimits,
+    /// Function depths are counted from the function itself.
+    pub function: MetricLimits,
+}
+
+impl MetricLimits {
+    /// Takes each limit from `self`, falling back to `fallback` for the
+    /// limits that are not set.
+    pub fn or(&self, fallback: &MetricLimits) -> MetricLimits {
+        MetricLimits {
+            maximum_depth: self.maximum_depth.or(fallback.maximum_depth),
+            average_depth: self.average_depth.or(fallback.average_depth),
+            cyclomatic_complexity: self
+                .cyclomatic_complexity
+                .or(fallback.cyclomatic_complexity),
+            cognitive_complexity: self.cognitive_complexity.or(fallback.cognitive_complexity),
+        }
+    }
+}
+
+impl Limits {
+    /// Whether no limit has been set at all.
+    pub fn is_empty(&self) -> bool {
+        *self == Limits::default()
+    }
+
+    /// Takes each limit from `self`, falling back to `fallback` for the
+    /// limits that are not set.
+    pub fn or(&self, fallback: &Limits) -> Limits {
+        Limits {
+            file: self.file.or(&fallback.file),
+            function: self.function.or(&fallback.function),
+        }
+    }
+}
+
+/// A metric that went over its limit.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Violation {
+    #[serde(rename = "file")]
+    pub path: String,
+    /// The qualified name of the function, unless the whole file is over
+    /// the limit.
+    pub function: Option<String>,
+    /// The name of the metric, as used in the JSON output.
+    pub metric: &'static str,
+    pub value: f64,
+    pub limit: f64,
+    /// The deepest node for depth limits, or else the start of the
+    /// function (or file).
+    pub location: Position,
+    /// The code the violation is about: the deepest node for depth limits,
+    /// or else the function, and `None` for a whole file.
+    pub span: Option<Span>,
+}
+
+/// Lists every way in which `file`, or a function in it, goes over `limits`.
+pub fn check(file: &FileReport, limits: &Limits) -> Vec<Violation> {
+    let start_of_file = Position { line: 1, column: 1 };
+    let mut violations = Vec::new();
+    check_metrics(
+        &file.report.metrics,
+        &limits.file,
+        start_of_file,
+        None,
+        &mut |metric, value, limit, location, span| {
+            violations.push(Violation {
+                path: file.path.clone(),
+                function: None,
+                metric,
+                value,
+                limit,
+                location,
+                span,
+            })
+        },
+    );
+    for function in &file.report.functions {
+        check_metrics(
+            &function.metrics,
+            &limits.function,
+            function.span.start,
+            Some(function.span),
+            &mut |metric, value, limit, location, span| {
+                violations.push(Violation {
+                    path: file.path.clone(),
+                    function: Some(function.name.clone()),
+                    metric,
+                    value,
+                    limit,
+                    location,
+                    span,
+                })
+            },
+        );
+    }
+    violations
+}
+
+fn check_metrics<F>(
+    metrics: &Metrics,
+    limits: &MetricLimits,
+    start: Position,
+    extent: Option<Span>,
+    report: &mut F,
+) where
+    F: FnMut(&'static str, f64, f64, Position, Option<Span>),
+{
+    let deepest = metrics.deepest_node.map_or(start, |span| span.start);
+    let deepest_span = metrics.deepest_node.or(extent);
+    let checks = [
+        (
+            "maximum_depth",
+            Some(metrics.maximum_depth as f64),
+            limits.maximum_depth.map(|limit| limit as f64),
+            deepest,
+            deepest_span,
+        ),
+        (
+            "average_depth",
+            Some(metrics.average_depth),
+            limits.average_depth,
+            start,
+            extent,
+        ),
+        (
+            "cyclomatic_complexity",
+            metrics.cyclomatic_complexity.map(|value| value as f64),
+            limits.cyclomatic_complexity.map(|limit| limit as f64),
+            start,
+            extent,
+        ),
+        (
+            "cognitive_complexity",
+            metrics.cognitive_complexity.map(|value| value as f64),
+            limits.cognitive_complexity.map(|limit| limit as f64),
+            start,
+            extent,
+        ),
+    ];
+    for (metric, value, limit, location, span) in checks {
+        if let (Some(value), Some(limit)) = (value, limit) {
+            if value > limit {
+                report(metric, value, limit, location, span);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::language::Language;
+    use crate::project::file_report;
+
+    #[test]
+    fn test_check_reports_each_exceeded_limit() {
+        let file = file_report("a", "if a:\n    if b:\n        pass\n", Language::Python);
+        let limits = Limits {
+            file: MetricLimits {
+                maximum_depth: Some(2),
+                average_depth: Some(100.0),
+                cyclomatic_complexity: Some(2),
+                cognitive_complexity: None,
+            },
+            ..Default::default()
+        };
+        let violations = check(&file, &limits);
+        let metrics: Vec<&str> = violations.iter().map(|v| v.metric).collect();
+        assert_eq!(metrics, vec!["maximum_depth", "cyclomatic_complexity"]);
+        // The deepest node is the `pass` on the third line
+        assert_eq!(violations[0].location, Position { line: 3, column: 9 });
+        assert_eq!(violations[1].value, 3.0);
+        assert_eq!(violations[1].limit, 2.0);
+        assert_eq!(violations[1].location, Position { line: 1, column: 1 });
+    }
+
+    #[test]
+    fn test_check_functions() {
+        let source = "def small():\n    pass\n\ndef large(a):\n    if a:\n        if a > 1:\n            return 1\n";
+        let file = file_report("a", source, Language::Python);
+        let limits = Limits {
+            function: MetricLimits {
+                cognitive_complexity: Some(2),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let violations = check(&file, &limits);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].function.as_deref(), Some("large"));
+        assert_eq!(violations[0].value, 3.0);
+        assert_eq!(violations[0].location, Position { line: 4, column: 1 });
+    }
+
+    #[test]
+    fn test_check_skips_unset_and_unavailable_limits() {
+        let file = file_report("a", "{\"a\": [1]}", Language::Json);
+        let limits = Limits {
+            file: MetricLimits {
+                cyclomatic_complexity: Some(0),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        assert!(check(&file, &limits).is_empty());
+        assert!(Limits::default().is_empty());
+    }
+}
@@ -0,0 +1,410 @@
+/*  A module for describing plexity's command-line interface: its
+subcommands, their arguments and the flags shared between them.  */
+
+use std::path::PathBuf;
+
+use clap::builder::{PossibleValuesParser, TypedValueParser};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use clap_complete::Shell;
+use plexity::check::{Limits, MetricLimits};
+use plexity::output::Format;
+use plexity::trace::LineRange;
+use plexity::{baseline, hotspots};
+use plexity::{Language, Metric};
+
+/// Measure the complexity of source code through the depth of its syntax tree.
+#[derive(Debug, Parser)]
+#[command(name = "plexity", version, arg_required_else_help = true)]
+#[command(args_conflicts_with_subcommands = true)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Running without a subcommand is the same as running `analyze`
+    #[command(flatten)]
+    pub analyze: AnalyzeArgs,
+
+    /// Read settings from this file instead of the nearest plexity.toml
+    #[arg(long, global = true, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+
+    #[command(flatten)]
+    pub verbosity: Verbosity,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Print the scorecard for files and directories
+    Analyze(AnalyzeArgs),
+    /// Print the syntax tree of a file
+    Tree(TreeArgs),
+    /// Compare the metrics of two files side by side
+    Compare(CompareArgs),
+    /// Show how the functions changed between two git revisions moved
+    Diff(DiffArgs),
+    /// Follow the metrics of a path over its git history
+    History(HistoryArgs),
+    /// List the supported languages and the metrics available for each
+    Languages,
+    /// Fail when files go over complexity limits
+    Check(CheckArgs),
+    /// Record the current metrics, so that check only fails on regressions
+    Baseline(BaselineArgs),
+    /// Generate a shell completion script
+    Completions {
+        /// The shell to generate completions for
+        shell: Shell,
+    },
+}
+
+#[derive(Debug, Args)]
+pub struct AnalyzeArgs {
+    /// Files or directories to analyse, optionally followed by a language
+    #[arg(value_name = "PATH")]
+    pub paths: Vec<String>,
+
+    #[command(flatten)]
+    pub language: LanguageArg,
+
+    #[command(flatten)]
+    pub format: FormatArg,
+
+    #[command(flatten)]
+    pub walk: WalkArgs,
+
+    /// Include every node of the syntax tree in the JSON output
+    #[arg(long)]
+    pub nodes: bool,
+
+    /// With --format csv or tsv, write one row per file, function or node
+    #[arg(long, value_enum, default_value_t = Rows::Files)]
+    pub rows: Rows,
+
+    /// Only report these optional metrics, such as cyclomatic,halstead
+    #[arg(long, value_name = "METRICS", value_delimiter = ',')]
+    pub metrics: Option<Vec<Metric>>,
+
+    /// List the most deeply nested regions of code, or as many as given
+    /// with --hotspots=COUNT
+    #[arg(long, value_name = "COUNT", num_args = 0..=1, require_equals = true)]
+    pub hotspots: Option<Option<usize>>,
+
+    /// Also write an HTML report, with the source shaded by depth, to this file
+    #[arg(long, value_name = "PATH")]
+    pub html: Option<PathBuf>,
+
+    #[command(flatten)]
+    pub trace: TraceArgs,
+}
+
+/// Options for printing the nodes visited while traversing each file
+#[derive(Debug, Args)]
+pub struct TraceArgs {
+    /// Print a line for each node visited in the syntax tree
+    #[arg(long)]
+    pub trace: bool,
+
+    /// Only trace nodes at least this deep
+    #[arg(long, value_name = "DEPTH", requires = "trace")]
+    pub trace_min_depth: Option<usize>,
+
+    /// Only trace nodes of this kind, such as if_statement (repeatable)
+    #[arg(long, value_name = "KIND", requires = "trace")]
+    pub trace_kind: Vec<String>,
+
+    /// Only trace nodes on these lines, such as 10-20 or 10-
+    #[arg(long, value_name = "LINES", requires = "trace")]
+    pub trace_lines: Option<LineRange>,
+
+    /// Print the trace as text or as JSON Lines
+    #[arg(
+        long,
+        value_name = "FORMAT",
+        default_value = "text",
+        requires = "trace",
+        value_parser = trace_format_parser()
+    )]
+    pub trace_format: Format,
+}
+
+#[derive(Debug, Args)]
+pub struct TreeArgs {
+    /// The file to parse
+    pub path: PathBuf,
+
+    #[command(flatten)]
+    pub language: LanguageArg,
+
+    /// How to print the tree
+    #[arg(short, long, value_enum, default_value_t = TreeFormat::Pretty)]
+    pub format: TreeFormat,
+
+    /// When to colour the pretty tree by depth
+    #[arg(
+        long,
+        visible_alias = "color",
+        value_name = "WHEN",
+        value_enum,
+        default_value_t = Colour::Auto
+    )]
+    pub colour: Colour,
+
+    /// Leave out anonymous nodes, such as punctuation and keywords
+    #[arg(long)]
+    pub named_only: bool,
+
+    /// Leave out nodes deeper than this
+    #[arg(long, value_name = "DEPTH")]
+    pub max_depth: Option<usize>,
+
+    /// Only include nodes on these lines, such as 10-20 or 10-
+    #[arg(long, value_name = "LINES")]
+    pub lines: Option<LineRange>,
+}
+
+/// The ways that a syntax tree can be printed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum TreeFormat {
+    /// An indented outline, one node per line
+    Pretty,
+    /// The S-expression printed by tree-sitter
+    Sexp,
+    /// A Graphviz graph, with nodes coloured by depth
+    Dot,
+    /// A Mermaid flowchart, with nodes coloured by depth
+    Mermaid,
+}
+
+/// When to colour terminal output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Colour {
+    /// When printing to a terminal, unless NO_COLOR is set
+    Auto,
+    Always,
+    Never,
+}
+
+#[derive(Debug, Args)]
+pub struct CompareArgs {
+    /// The file to compare from
+    pub before: PathBuf,
+
+    /// The file to compare to
+    pub after: PathBuf,
+
+    #[command(flatten)]
+    pub language: LanguageArg,
+
+    #[command(flatten)]
+    pub format: FormatArg,
+}
+
+#[derive(Debug, Args)]
+pub struct DiffArgs {
+    /// The revision to compare from, such as main or HEAD~3
+    pub base: String,
+
+    /// The revision to compare to
+    #[arg(default_value = "HEAD")]
+    pub head: String,
+
+    #[command(flatten)]
+    pub language: LanguageArg,
+
+    #[command(flatten)]
+    pub format: FormatArg,
+}
+
+#[derive(Debug, Args)]
+pub struct HistoryArgs {
+    /// The file or directory to follow
+    pub path: PathBuf,
+
+    #[command(flatten)]
+    pub language: LanguageArg,
+
+    /// Only measure every Nth commit, counting back from the newest
+    #[arg(long, value_name = "N", default_value_t = 1)]
+    pub every: usize,
+
+    /// Only measure the newest COUNT of the sampled commits
+    #[arg(long, value_name = "COUNT")]
+    pub limit: Option<usize>,
+
+    /// The output format
+    #[arg(short, long, value_enum, default_value_t = SeriesFormat::Csv)]
+    pub format: SeriesFormat,
+}
+
+/// The formats that a time series can be written in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SeriesFormat {
+    Csv,
+    Tsv,
+    Json,
+}
+
+/// What each row stands for in tabular output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Rows {
+    Files,
+    Functions,
+    Nodes,
+}
+
+#[derive(Debug, Args)]
+pub struct CheckArgs {
+    /// Files or directories to check
+    #[arg(value_name = "PATH", required = true)]
+    pub paths: Vec<PathBuf>,
+
+    #[command(flatten)]
+    pub language: LanguageArg,
+
+    #[command(flatten)]
+    pub format: FormatArg,
+
+    #[command(flatten)]
+    pub walk: WalkArgs,
+
+    #[command(flatten)]
+    pub thresholds: Thresholds,
+
+    /// Only fail on files and functions that are new or worse than in this
+    /// baseline
+    #[arg(long, value_name = "PATH")]
+    pub baseline: Option<PathBuf>,
+
+    /// Also write the violations to this file as a SARIF 2.1.0 log
+    #[arg(long, value_name = "PATH")]
+    pub sarif: Option<PathBuf>,
+}
+
+#[derive(Debug, Args)]
+pub struct BaselineArgs {
+    /// Files or directories to record
+    #[arg(value_name = "PATH", required = true)]
+    pub paths: Vec<PathBuf>,
+
+    #[command(flatten)]
+    pub language: LanguageArg,
+
+    #[command(flatten)]
+    pub walk: WalkArgs,
+
+    /// Where to write the baseline
+    #[arg(short, long, value_name = "PATH", default_value = baseline::DEFAULT_FILE_NAME)]
+    pub output: PathBuf,
+}
+
+/// The limits that `check` holds each file, and each function, to
+#[derive(Debug, Args)]
+pub struct Thresholds {
+    /// Fail when any node is nested deeper than this
+    #[arg(long, value_name = "DEPTH")]
+    pub max_depth: Option<usize>,
+
+    /// Fail when a file's average depth is above this
+    #[arg(long, value_name = "DEPTH")]
+    pub max_average_depth: Option<f64>,
+
+    /// Fail when a file's cyclomatic complexity is above this
+    #[arg(long, value_name = "COMPLEXITY")]
+    pub max_cyclomatic: Option<usize>,
+
+    /// Fail when a file's cognitive complexity is above this
+    #[arg(long, value_name = "COMPLEXITY")]
+    pub max_cognitive: Option<usize>,
+
+    /// Fail when any node is nested deeper than this within its function
+    #[arg(long, value_name = "DEPTH")]
+    pub max_function_depth: Option<usize>,
+
+    /// Fail when a function's average depth is above this
+    #[arg(long, value_name = "DEPTH")]
+    pub max_function_average_depth: Option<f64>,
+
+    /// Fail when a function's cyclomatic complexity is above this
+    #[arg(long, value_name = "COMPLEXITY")]
+    pub max_function_cyclomatic: Option<usize>,
+
+    /// Fail when a function's cognitive complexity is above this
+    #[arg(long, value_name = "COMPLEXITY")]
+    pub max_function_cognitive: Option<usize>,
+}
+
+impl AnalyzeArgs {
+    /// How many hotspots to list, when they are wanted.
+    pub fn hotspot_count(&self) -> Option<usize> {
+        self.hotspots
+            .map(|count| count.unwrap_or(hotspots::DEFAULT_HOTSPOT_COUNT))
+    }
+}
+
+impl Thresholds {
+    pub fn limits(&self) -> Limits {
+        Limits {
+            file: MetricLimits {
+                maximum_depth: self.max_depth,
+                average_depth: self.max_average_depth,
+                cyclomatic_complexity: self.max_cyclomatic,
+                cognitive_complexity: self.max_cognitive,
+            },
+            function: MetricLimits {
+                maximum_depth: self.max_function_depth,
+                average_depth: self.max_function_average_depth,
+                cyclomatic_complexity: self.max_function_cyclomatic,
+                cognitive_complexity: self.max_function_cognitive,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct LanguageArg {
+    /// Parse every file with this language instead of detecting it
+    #[arg(short, long, value_name = "LANGUAGE")]
+    pub language: Option<Language>,
+}
+
+/// Options for choosing the files to analyse in directories
+#[derive(Debug, Args)]
+pub struct WalkArgs {
+    /// Leave out files and directories matching this glob (repeatable)
+    #[arg(long, value_name = "GLOB")]
+    pub exclude: Vec<String>,
+
+    /// Also analyse hidden directories and files listed in .gitignore,
+    /// .ignore and .plexityignore
+    #[arg(long)]
+    pub no_ignore: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct FormatArg {
+    /// The output format [default: text]
+    #[arg(short, long, value_parser = format_parser())]
+    pub format: Option<Format>,
+}
+
+fn format_parser() -> impl TypedValueParser<Value = Format> {
+    PossibleValuesParser::new(Format::ALL.map(Format::name))
+        .map(|name| Format::from_name(&name).unwrap())
+}
+
+// A trace is printed a line at a time, which only text and JSON Lines suit
+fn trace_format_parser() -> impl TypedValueParser<Value = Format> {
+    PossibleValuesParser::new([Format::Text, Format::Json].map(Format::name))
+        .map(|name| Format::from_name(&name).unwrap())
+}
+
+#[derive(Debug, Args)]
+pub struct Verbosity {
+    /// Print only scorecards and problems, without file headers
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    pub quiet: bool,
+
+    /// Also report which language each file was parsed as
+    #[arg(short, long, global = true)]
+    pub verbose: bool,
+}
@@ -0,0 +1,317 @@
+/*  A module for calculating SonarSource's cognitive complexity, which
+adds a penalty for each break in the linear flow of the code that grows
+with the depth at which the break is nested.  */
+
+use crate::functions;
+use crate::language::Language;
+use crate::suppress::Suppressions;
+use tree_sitter::Node;
+
+// The structures that break the linear flow, are penalised for their
+// nesting level, and increase the nesting level of their contents
+fn nesting_kinds(language: Language) -> &'static [&'static str] {
+    match language {
+        Language::Java => &[
+            "if_statement",
+            "for_statement",
+            "enhanced_for_statement",
+            "while_statement",
+            "do_statement",
+            "switch_expression",
+            "catch_clause",
+            "ternary_expression",
+        ],
+        Language::JavaScript | Language::TypeScript | Language::Tsx => &[
+            "if_statement",
+            "for_statement",
+            "for_in_statement",
+            "while_statement",
+            "do_statement",
+            "switch_statement",
+            "catch_clause",
+            "ternary_expression",
+        ],
+        Language::Python => &[
+            "if_statement",
+            "for_statement",
+            "while_statement",
+            "match_statement",
+            "except_clause",
+            "conditional_expression",
+        ],
+        Language::Rust => &[
+            "if_expression",
+            "for_expression",
+            "while_expression",
+            "loop_expression",
+            "match_expression",
+        ],
+        _ => &[],
+    }
+}
+
+// The anonymous functions that only increase the nesting level
+fn lambda_kinds(language: Language) -> &'static [&'static str] {
+    match language {
+        Language::Java => &["lambda_expression"],
+        Language::Python => &["lambda"],
+        Language::Rust => &["closure_expression"],
+        _ => &[],
+    }
+}
+
+// The jumps that add to the complexity when they target a label
+fn jump_kinds(language: Language) -> &'static [&'static str] {
+    match language {
+        Language::Java | Language::JavaScript | Language::TypeScript | Language::Tsx => {
+            &["break_statement", "continue_statement"]
+        }
+        Language::Rust => &["break_expression", "continue_expression"],
+        _ => &[],
+    }
+}
+
+/// Whether cognitive complexity can be calculated for `language`.
+pub fn supported(language: Language) -> bool {
+    !nesting_kinds(language).is_empty()
+}
+
+/// Calculates the cognitive complexity of the code below `node`. When
+/// `function` is given, `node` is treated as the body of that function,
+/// so that nested functions are penalised and recursive calls are counted.
+/// Subtrees covered by `suppressions` are skipped.
+pub fn cognitive_complexity(
+    node: Node,
+    source: &str,
+    language: Language,
+    function: Option<&str>,
+    suppressions: &Suppressions,
+) -> Option<usize> {
+    if !supported(language) {
+        return None;
+    }
+    let mut walk = Walk {
+        source,
+        language,
+        suppressions,
+        function: function.map(|name| short_name(name).to_string()),
+        complexity: 0,
+    };
+    walk.visit(node, 0);
+    Some(walk.complexity)
+}
+
+struct Walk<'a> {
+    source: &'a str,
+    language: Language,
+    suppressions: &'a Suppressions,
+    // The unqualified name of the innermost enclosing function
+    function: Option<String>,
+    complexity: usize,
+}
+
+impl Walk<'_> {
+    fn visit(&mut self, node: Node, nesting: usize) {
+        for i in 0..node.named_child_count() {
+            let child = node.named_child(i).unwrap();
+            if self.suppressions.covers(child) {
+                continue;
+            }
+            let kind = child.kind();
+
+            if functions::function_kinds(self.language).contains(&kind) {
+                // Functions nested inside another function are penalised
+                let enclosing = self.function.take();
+                let nesting = if enclosing.is_some() { nesting + 1 } else { 0 };
+                self.function = Some(functions::function_name(child, self.source));
+                self.visit(child, nesting);
+                self.function = enclosing;
+            } else if lambda_kinds(self.language).contains(&kind) {
+                self.visit(child, nesting + 1);
+            } else if is_else_if(child) || kind == "elif_clause" {
+                self.complexity += 1;
+                self.visit(child, nesting);
+            } else if nesting_kinds(self.language).contains(&kind) {
+                // A braceless Java else can hold a nesting statement directly
+                if is_java_else(child) {
+                    self.complexity += 1;
+                }
+                self.complexity += 1 + nesting;
+                self.visit(child, nesting + 1);
+            } else if kind == "else_clause" || is_java_else(child) {
+                if !is_else_if_wrapper(child) {
+                    self.complexity += 1;
+                }
+                self.visit(child, nesting);
+            } else if jump_kinds(self.language).contains(&kind) {
+                if has_label(child) {
+                    self.complexity += 1;
+                }
+                self.visit(child, nesting);
+            } else if let Some(operator) = boolean_operator(child, self.language) {
+                // Only the first of a sequence of like operators is counted
+                let parent_operator = boolean_operator(node, self.language);
+                if parent_operator != Some(operator) {
+                    self.complexity += 1;
+                }
+                self.visit(child, nesting);
+            } else {
+                if self.is_recursive_call(child) {
+                    self.complexity += 1;
+                }
+                self.visit(child, nesting);
+            }
+        }
+    }
+
+    fn is_recursive_call(&self, node: Node) -> bool {
+        let callee = match node.kind() {
+            "call" | "call_expression" => node.child_by_field_name("function"),
+            "method_invocation" => node.child_by_field_name("name"),
+            _ => return false,
+        };
+        let callee = match callee {
+            Some(callee) => callee,
+            None => return false,
+        };
+        // Reduce `self.name`, `this.name` and `Self::name` to `name`
+        let name = ["attribute", "property", "field", "name"]
+            .iter()
+            .find_map(|field| callee.child_by_field_name(field))
+            .unwrap_or(callee);
+        let name = name.utf8_text(self.source.as_bytes()).unwrap_or_default();
+        self.function.as_deref() == Some(name)
+    }
+}
+
+fn short_name(qualified: &str) -> &str {
+    qualified.rsplit('.').next().unwrap_or(qualified)
+}
+
+// An `if` that directly follows an `else`, which is not penalised for nesting
+fn is_else_if(node: Node) -> bool {
+    if !matches!(node.kind(), "if_statement" | "if_expression") {
+        return false;
+    }
+    match node.parent() {
+        Some(parent) if parent.kind() == "else_clause" => true,
+        Some(parent) if parent.kind() == "if_statement" => {
+            parent.child_by_field_name("alternative") == Some(node)
+        }
+        _ => false,
+    }
+}
+
+// An `else` clause that only wraps an `else if`, which is counted instead
+fn is_else_if_wrapper(node: Node) -> bool {
+    node.named_child_count() == 1 && is_else_if(node.named_child(0).unwrap())
+}
+
+// Java has no `else_clause`; the statement after `else`, with or without
+// braces, is the alternative of the `if`
+fn is_java_else(node: Node) -> bool {
+    match node.parent() {
+        Some(parent) if parent.kind() == "if_statement" => {
+            node.kind() != "if_statement" && parent.child_by_field_name("alternative") == Some(node)
+        }
+        _ => false,
+    }
+}
+
+fn has_label(node: Node) -> bool {
+    (0..node.named_child_count())
+        .filter_map(|i| node.named_child(i))
+        .any(|child| {
+            matches!(
+                child.kind(),
+                "identifier" | "statement_identifier" | "loop_label"
+            )
+        })
+}
+
+fn boolean_operator(node: Node, language: Language) -> Option<&'static str> {
+    let operator = match node.kind() {
+        "boolean_operator" | "binary_expression" => node.child_by_field_name("operator")?,
+        _ => return None,
+    };
+    let operators: &[&'static str] = match language {
+        Language::Python => &["and", "or"],
+        Language::JavaScript | Language::TypeScript | Language::Tsx => &["&&", "||", "??"],
+        _ => &["&&", "||"],
+    };
+    operators
+        .iter()
+        .find(|candidate| **candidate == operator.kind())
+        .copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::language::select_parser;
+
+    fn cognitive(source: &str, language: Language) -> Option<usize> {
+        let tree = select_parser(language)
+            .unwrap()
+            .parse(source, None)
+            .unwrap();
+        let suppressions = Suppressions::find(tree.root_node(), source, language);
+        cognitive_complexity(tree.root_node(), source, language, None, &suppressions)
+    }
+
+    #[test]
+    fn test_cognitive_python_nesting() {
+        // if (+1), nested for (+2), nested if (+3), elif (+1), else (+1)
+        let source = "def f(a):\n    if a:\n        for b in a:\n            if b:\n                pass\n            elif a:\n                pass\n            else:\n                pass\n";
+        assert_eq!(cognitive(source, Language::Python), Some(8));
+    }
+
+    #[test]
+    fn test_cognitive_python_boolean_sequences_and_recursion() {
+        // if (+1), `and and` (+1), `or` (+1), recursive call (+1)
+        let source = "def f(a, b, c):\n    if a and b and c or a:\n        return f(a, b, c)\n";
+        assert_eq!(cognitive(source, Language::Python), Some(4));
+    }
+
+    #[test]
+    fn test_cognitive_java_else_if() {
+        // if (+1), else if (+1), else (+1), nested ternary (+2)
+        let source = "class A { int f(int a) { if (a > 1) { return 1; } else if (a > 0) { return a > 0 ? 1 : 2; } else { return 0; } } }";
+        assert_eq!(cognitive(source, Language::Java), Some(5));
+    }
+
+    #[test]
+    fn test_cognitive_java_braceless_else() {
+        // if (+1), else (+1)
+        let source = "class A { int f(int a) { if (a > 0) return 1; else return 0; } }";
+        assert_eq!(cognitive(source, Language::Java), Some(2));
+    }
+
+    #[test]
+    fn test_cognitive_java_braceless_else_loop() {
+        // if (+1), else (+1), while (+2 as it is nested in the if)
+        let source = "class A { void f() { if (a) {} else while (b) {} } }";
+        assert_eq!(cognitive(source, Language::Java), Some(4));
+        let braced = "class A { void f() { if (a) {} else { while (b) {} } } }";
+        assert_eq!(cognitive(braced, Language::Java), Some(4));
+    }
+
+    #[test]
+    fn test_cognitive_javascript_labelled_jump() {
+        // outer for (+1), inner for (+2), labelled continue (+1)
+        let source = "function f(a) { outer: for (const x of a) { for (const y of x) { continue outer; } } }";
+        assert_eq!(cognitive(source, Language::JavaScript), Some(4));
+    }
+
+    #[test]
+    fn test_cognitive_rust_match_and_closure() {
+        // match (+1), if inside a closure nested in a match arm (+3), else (+1)
+        let source = "fn f(a: u8) { match a { 1 => { let g = |b: u8| if b > 0 { 1 } else { 2 }; } _ => {} } }";
+        assert_eq!(cognitive(source, Language::Rust), Some(5));
+    }
+
+    #[test]
+    fn test_cognitive_unsupported_language() {
+        assert_eq!(cognitive("int main() { return 0; }", Language::C), None);
+    }
+}
@@ -0,0 +1,282 @@
+/*  A module for reading the `plexity.toml` configuration file, which sets
+project-wide defaults for the command-line options: the output format, the
+metrics to report, globs of files to include or exclude, custom mappings
+from file names to languages, and thresholds for `check`.
+
+Globs are matched against paths relative to the directory holding the file.
+Thresholds use the names of the `check` flags, and those in a table named
+after a language take priority over the ones for every language.  */
+
+use crate::check::{Limits, MetricLimits};
+use crate::detect::Mappings;
+use crate::error::ConfigError;
+use crate::language::Language;
+use crate::output::Format;
+use crate::report::Metric;
+use crate::walk::{PathGlobs, Selection};
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The name of the configuration file.
+pub const FILE_NAME: &str = "plexity.toml";
+
+/// The settings read from a configuration file. Every setting is optional,
+/// and the default configuration changes nothing.
+#[derive(Debug, Clone, Default)]
+pub struct Config {
+    /// The file the settings were read from, if any.
+    pub path: Option<PathBuf>,
+    pub format: Option<Format>,
+    /// The optional metrics to report, or all of them when not set.
+    pub metrics: Option<Vec<Metric>>,
+    pub include: Option<PathGlobs>,
+    pub exclude: Option<PathGlobs>,
+    pub mappings: Mappings,
+    /// The limits for files in any language.
+    pub thresholds: Limits,
+    /// Limits that take priority over `thresholds` for some languages.
+    pub language_thresholds: HashMap<Language, Limits>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct Settings {
+    format: Option<String>,
+    metrics: Option<Vec<Metric>>,
+    include: Vec<String>,
+    exclude: Vec<String>,
+    languages: BTreeMap<String, String>,
+    thresholds: toml::Table,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+struct ThresholdSettings {
+    max_depth: Option<usize>,
+    max_average_depth: Option<f64>,
+    max_cyclomatic: Option<usize>,
+    max_cognitive: Option<usize>,
+    max_function_depth: Option<usize>,
+    max_function_average_depth: Option<f64>,
+    max_function_cyclomatic: Option<usize>,
+    max_function_cognitive: Option<usize>,
+}
+
+impl ThresholdSettings {
+    fn limits(self) -> Limits {
+        Limits {
+            file: MetricLimits {
+                maximum_depth: self.max_depth,
+                average_depth: self.max_average_depth,
+                cyclomatic_complexity: self.max_cyclomatic,
+                cognitive_complexity: self.max_cognitive,
+            },
+            function: MetricLimits {
+                maximum_depth: self.max_function_depth,
+                average_depth: self.max_function_average_depth,
+                cyclomatic_complexity: self.max_function_cyclomatic,
+                cognitive_complexity: self.max_function_cognitive,
+            },
+        }
+    }
+}
+
+impl Config {
+    /// Finds the configuration file in `start` or the nearest directory
+    /// above it and reads it, or returns the default configuration when
+    /// there is none.
+    pub fn discover(start: &Path) -> Result<Config, ConfigError> {
+        for directory in start.ancestors() {
+            let path = directory.join(FILE_NAME);
+            if path.is_file() {
+                return Config::load(&path);
+            }
+        }
+        Ok(Config::default())
+    }
+
+    /// Reads the configuration file at `path`.
+    pub fn load(path: &Path) -> Result<Config, ConfigError> {
+        let text = fs::read_to_string(path).map_err(|err| ConfigError::new(path, err))?;
+        let root = path.parent().unwrap_or(Path::new("."));
+        let config =
+            Config::parse(&text, root).map_err(|message| ConfigError::new(path, message))?;
+        Ok(Config {
+            path: Some(path.to_path_buf()),
+            ..config
+        })
+    }
+
+    // Reads the settings in `text`, with globs relative to `root`
+    fn parse(text: &str, root: &Path) -> Result<Config, String> {
+        let settings: Settings = toml::from_str(text).map_err(|err| err.message().to_string())?;
+
+        let format = settings
+            .format
+            .map(|name| {
+                Format::from_name(&name)
+                    .ok_or_else(|| format!("\"{name}\" is not an output format"))
+            })
+            .transpose()?;
+        let globs = |patterns: &[String]| {
+            if patterns.is_empty() {
+                return Ok(None);
+            }
+            PathGlobs::new(root, patterns)
+                .map(Some)
+                .map_err(|err| err.to_string())
+        };
+        let mappings = settings
+            .languages
+            .into_iter()
+            .map(|(glob, name)| Ok((glob, name.parse().map_err(|err| format!("{err}"))?)))
+            .collect::<Result<Vec<(String, Language)>, String>>()?;
+
+        let mut defaults = toml::Table::new();
+        let mut language_thresholds = HashMap::new();
+        for (key, value) in settings.thresholds {
+            match value {
+                toml::Value::Table(table) => {
+                    let language: Language = key.parse().map_err(|err| format!("{err}"))?;
+                    language_thresholds.insert(language, threshold_limits(table)?);
+                }
+                value => {
+                    defaults.insert(key, value);
+                }
+            }
+        }
+
+        Ok(Config {
+            path: None,
+            format,
+            metrics: settings.metrics,
+            include: globs(&settings.include)?,
+            exclude: globs(&settings.exclude)?,
+            mappings: Mappings::new(&mappings).map_err(|err| err.to_string())?,
+            thresholds: threshold_limits(defaults)?,
+            language_thresholds,
+        })
+    }
+
+    /// The limits that files written in `language` are held to.
+    pub fn limits(&self, language: Language) -> Limits {
+        match self.language_thresholds.get(&language) {
+            Some(limits) => limits.or(&self.thresholds),
+            None => self.thresholds.clone(),
+        }
+    }
+
+    /// Whether any limit is set, for any language.
+    pub fn has_limits(&self) -> bool {
+        !self.thresholds.is_empty() || self.language_thresholds.values().any(|l| !l.is_empty())
+    }
+
+    /// The root of the project: the directory holding the configuration
+    /// file, or else the working directory.
+    pub fn root(&self) -> &Path {
+        self.path
+            .as_deref()
+            .and_then(Path::parent)
+            .filter(|directory| !directory.as_os_str().is_empty())
+            .unwrap_or(Path::new("."))
+    }
+
+    /// The files to analyse when walking directories, limited to
+    /// `language` when it is given.
+    pub fn selection(&self, language: Option<Language>) -> Selection {
+        Selection {
+            language,
+            mappings: self.mappings.clone(),
+            include: self.include.clone(),
+            exclude: self.exclude.iter().cloned().collect(),
+            include_ignored: false,
+        }
+    }
+}
+
+fn threshold_limits(table: toml::Table) -> Result<Limits, String> {
+    let settings: ThresholdSettings = table
+        .try_into()
+        .map_err(|err: toml::de::Error| format!("in [thresholds]: {}", err.message()))?;
+    Ok(settings.limits())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = r#"
+format = "json"
+metrics = ["cyclomatic", "functions"]
+exclude = ["vendor/**"]
+
+[languages]
+"*.inc" = "php"
+
+[thresholds]
+max-depth = 12
+max-function-cyclomatic = 10
+
+[thresholds.py]
+max-depth = 8
+max-average-depth = 4
+"#;
+
+    #[test]
+    fn test_parse_every_setting() {
+        let config = Config::parse(EXAMPLE, Path::new("/repo")).unwrap();
+        assert_eq!(config.format, Some(Format::Json));
+        assert_eq!(
+            config.metrics,
+            Some(vec![Metric::Cyclomatic, Metric::Functions])
+        );
+        assert!(config.include.is_none());
+        let exclude = config.exclude.as_ref().unwrap();
+        assert!(exclude.is_match(Path::new("/repo/vendor/lib/a.py")));
+        assert!(!exclude.is_match(Path::new("/repo/src/a.py")));
+        assert_eq!(
+            config.mappings.language(Path::new("lib/header.inc")),
+            Some(Language::Php)
+        );
+
+        let python = config.limits(Language::Python);
+        assert_eq!(python.file.maximum_depth, Some(8));
+        assert_eq!(python.file.average_depth, Some(4.0));
+        assert_eq!(python.function.cyclomatic_complexity, Some(10));
+        let rust = config.limits(Language::Rust);
+        assert_eq!(rust.file.maximum_depth, Some(12));
+        assert_eq!(rust.file.average_depth, None);
+        assert!(config.has_limits());
+    }
+
+    #[test]
+    fn test_parse_rejects_mistakes() {
+        let root = Path::new(".");
+        assert!(Config::parse("formt = \"json\"", root).is_err());
+        assert!(Config::parse("format = \"xml\"", root).is_err());
+        assert!(Config::parse("metrics = [\"depth\"]", root).is_err());
+        assert!(Config::parse("[thresholds]\nmax-dpeth = 3", root).is_err());
+        let err = Config::parse("[thresholds.go]\nmax-depth = 3", root).unwrap_err();
+        assert!(err.contains("\"go\" is not a supported programming language"));
+    }
+
+    #[test]
+    fn test_discover_walks_up() {
+        let root =
+            std::env::temp_dir().join(format!("plexity-config-discover-{}", std::process::id()));
+        let nested = root.join("src").join("deep");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(root.join(FILE_NAME), "format = \"json\"\n").unwrap();
+
+        let config = Config::discover(&nested).unwrap();
+        assert_eq!(config.format, Some(Format::Json));
+        assert_eq!(config.path, Some(root.join(FILE_NAME)));
+
+        fs::write(root.join(FILE_NAME), "format = [\n").unwrap();
+        let err = Config::discover(&nested).unwrap_err();
+        assert_eq!(err.path, root.join(FILE_NAME));
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
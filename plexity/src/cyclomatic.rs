@@ -0,0 +1,170 @@
+/*  A module for recognising the decision points that make up a
+tree's cyclomatic complexity in each supported programming language.  */
+
+use crate::language::Language;
+use tree_sitter::Node;
+
+// The node kinds that open a new path through the code
+fn decision_kinds(language: Language) -> &'static [&'static str] {
+    match language {
+        Language::C => &[
+            "if_statement",
+            "for_statement",
+            "while_statement",
+            "do_statement",
+            "case_statement",
+            "conditional_expression",
+        ],
+        Language::Cpp => &[
+            "if_statement",
+            "for_statement",
+            "for_range_loop",
+            "while_statement",
+            "do_statement",
+            "case_statement",
+            "catch_clause",
+            "conditional_expression",
+        ],
+        Language::Java => &[
+            "if_statement",
+            "for_statement",
+            "enhanced_for_statement",
+            "while_statement",
+            "do_statement",
+            "switch_label",
+            "catch_clause",
+            "ternary_expression",
+        ],
+        Language::JavaScript | Language::TypeScript | Language::Tsx => &[
+            "if_statement",
+            "for_statement",
+            "for_in_statement",
+            "while_statement",
+            "do_statement",
+            "switch_case",
+            "catch_clause",
+            "ternary_expression",
+        ],
+        Language::Php => &[
+            "if_statement",
+            "else_if_clause",
+            "for_statement",
+            "foreach_statement",
+            "while_statement",
+            "do_statement",
+            "case_statement",
+            "catch_clause",
+            "conditional_expression",
+            "match_conditional_expression",
+        ],
+        Language::Python => &[
+            "if_statement",
+            "elif_clause",
+            "for_statement",
+            "while_statement",
+            "except_clause",
+            "with_statement",
+            "assert_statement",
+            "list_comprehension",
+            "set_comprehension",
+            "dictionary_comprehension",
+            "boolean_operator",
+        ],
+        Language::Rust => &[
+            "if_expression",
+            "while_expression",
+            "for_expression",
+            "match_arm",
+        ],
+        _ => &[],
+    }
+}
+
+// The operators that short-circuit a `binary_expression`
+fn boolean_operators(language: Language) -> &'static [&'static str] {
+    match language {
+        Language::C | Language::Cpp | Language::Java | Language::Rust => &["&&", "||"],
+        Language::JavaScript | Language::TypeScript | Language::Tsx => &["&&", "||", "??"],
+        Language::Php => &["&&", "||", "??", "and", "or", "xor"],
+        _ => &[],
+    }
+}
+
+/// Whether cyclomatic complexity can be calculated for `language`.
+pub fn supported(language: Language) -> bool {
+    !decision_kinds(language).is_empty()
+}
+
+/// Whether `node` is a decision point that adds to the cyclomatic complexity.
+pub fn is_decision_point(node: Node, language: Language) -> bool {
+    let kind = node.kind();
+
+    if kind == "binary_expression" {
+        return match node.child_by_field_name("operator") {
+            Some(operator) => boolean_operators(language).contains(&operator.kind()),
+            None => false,
+        };
+    }
+
+    decision_kinds(language).contains(&kind) && !is_default_branch(node)
+}
+
+// The `default` label of a switch (or a `_` match arm) is taken when no
+// other branch is, so it does not add a path of its own
+fn is_default_branch(node: Node) -> bool {
+    match node.kind() {
+        "case_statement" | "switch_label" => node.child(0).map(|c| c.kind()) == Some("default"),
+        "match_arm" => match node.child_by_field_name("pattern") {
+            Some(pattern) => pattern.child_count() == 1 && pattern.child(0).unwrap().kind() == "_",
+            None => false,
+        },
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::language::Language;
+
+    fn cyclomatic(source: &str, language: Language) -> Option<usize> {
+        crate::analyze(source, language)
+            .unwrap()
+            .metrics
+            .cyclomatic_complexity
+    }
+
+    #[test]
+    fn test_cyclomatic_c() {
+        let source = "int f(int a) { if (a && a > 1) { return 1; } switch (a) { case 1: break; default: break; } return a ? 2 : 3; }";
+        assert_eq!(cyclomatic(source, Language::C), Some(5));
+    }
+
+    #[test]
+    fn test_cyclomatic_java() {
+        let source = "class A { void f(int a) { for (int i = 0; i < a; i++) {} try {} catch (Exception e) {} while (a > 0 || a < -1) {} } }";
+        assert_eq!(cyclomatic(source, Language::Java), Some(5));
+    }
+
+    #[test]
+    fn test_cyclomatic_javascript() {
+        let source = "function f(a) { if (a) {} else if (a ?? 1) {} const b = a ? 1 : 2; }";
+        assert_eq!(cyclomatic(source, Language::JavaScript), Some(5));
+    }
+
+    #[test]
+    fn test_cyclomatic_php() {
+        let source = "<?php if ($a) {} elseif ($b and $c) {} foreach ($d as $e) {}";
+        assert_eq!(cyclomatic(source, Language::Php), Some(5));
+    }
+
+    #[test]
+    fn test_cyclomatic_rust() {
+        let source = "fn f(a: u8) { match a { 1 => {}, 2 => {}, _ => {} } if a > 1 && a < 5 {} }";
+        assert_eq!(cyclomatic(source, Language::Rust), Some(5));
+    }
+
+    #[test]
+    fn test_cyclomatic_unsupported_language() {
+        assert_eq!(cyclomatic("[table]\nkey = 1\n", Language::Toml), None);
+    }
+}
@@ -0,0 +1,290 @@
+/*  A module for working out which language a source file is written in,
+from its editor modelines, its shebang line, or its name and extension.  */
+
+use crate::language::Language;
+use globset::{Glob, GlobMatcher};
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+
+// How many lines at each end of a file are searched for modelines
+const MODELINE_LINES: usize = 5;
+
+// How many bytes at each end of a file are read to find its modelines
+const SAMPLE_BYTES: u64 = 1024;
+
+/// Detects the language of the file at `path` with the given `contents`.
+/// An editor modeline takes priority over a shebang line, which in turn
+/// takes priority over the file's name and extension.
+pub fn detect(path: &Path, contents: &str) -> Option<Language> {
+    modeline(contents)
+        .or_else(|| shebang(contents))
+        .or_else(|| Language::from_path(path))
+}
+
+/// Detects the language of the file at `path` in the same way as `detect`,
+/// reading only as much of its start and end as can hold a modeline or a
+/// shebang line.
+pub fn detect_file(path: &Path) -> io::Result<Option<Language>> {
+    let sample = read_sample(path, SAMPLE_BYTES)?;
+    Ok(detect(path, &String::from_utf8_lossy(&sample)))
+}
+
+/// Reads up to `bytes` from each end of the file at `path`, which is
+/// enough to find its modelines and shebang line without reading all of
+/// a large file. Short files are read whole.
+pub fn read_sample(path: &Path, bytes: u64) -> io::Result<Vec<u8>> {
+    let mut file = File::open(path)?;
+    let mut sample = Vec::new();
+    if file.metadata()?.len() <= 2 * bytes {
+        file.read_to_end(&mut sample)?;
+        return Ok(sample);
+    }
+    (&mut file).take(bytes).read_to_end(&mut sample)?;
+    let mut tail = Vec::new();
+    file.seek(SeekFrom::End(-(bytes as i64)))?;
+    file.read_to_end(&mut tail)?;
+    // The tail starts part way through a line, which is dropped
+    let start = tail
+        .iter()
+        .position(|&byte| byte == b'\n')
+        .map_or(tail.len(), |i| i + 1);
+    sample.push(b'\n');
+    sample.extend_from_slice(&tail[start..]);
+    Ok(sample)
+}
+
+/// Looks up a language by any of the names editors and interpreters use
+/// for it, such as `py`, `node` or `c++`.
+pub fn language_for_name(name: &str) -> Option<Language> {
+    let name = name.trim().to_lowercase();
+    let language = match name.as_str() {
+        "c" | "h" => Language::C,
+        "cpp" | "c++" | "cc" | "cxx" | "hpp" => Language::Cpp,
+        "css" => Language::Css,
+        "dockerfile" | "docker" => Language::Dockerfile,
+        "gitattributes" => Language::Gitattributes,
+        "html" | "htm" | "xhtml" => Language::Html,
+        "java" => Language::Java,
+        "javascript" | "js" | "jsx" | "node" | "nodejs" => Language::JavaScript,
+        "json" => Language::Json,
+        "markdown" | "md" => Language::Markdown,
+        "php" => Language::Php,
+        "python" | "py" => Language::Python,
+        "rust" | "rs" => Language::Rust,
+        "toml" => Language::Toml,
+        "typescript" | "ts" | "ts-node" | "deno" => Language::TypeScript,
+        "tsx" | "typescriptreact" => Language::Tsx,
+        _ => return None,
+    };
+    Some(language)
+}
+
+/// Custom mappings from file name globs (such as `*.pyw` or `SConstruct`)
+/// to languages, which take priority over every other way of detecting
+/// the language of a file.
+#[derive(Debug, Clone, Default)]
+pub struct Mappings {
+    globs: Vec<(GlobMatcher, Language)>,
+}
+
+impl Mappings {
+    /// Compiles the `(glob, language)` pairs, which are tried in order.
+    pub fn new(mappings: &[(String, Language)]) -> Result<Mappings, globset::Error> {
+        let globs = mappings
+            .iter()
+            .map(|(glob, language)| Ok((Glob::new(glob)?.compile_matcher(), *language)))
+            .collect::<Result<_, globset::Error>>()?;
+        Ok(Mappings { globs })
+    }
+
+    /// The language that the name of the file at `path` is mapped to.
+    pub fn language(&self, path: &Path) -> Option<Language> {
+        let file_name = path.file_name()?;
+        self.globs
+            .iter()
+            .find(|(glob, _)| glob.is_match(file_name))
+            .map(|(_, language)| *language)
+    }
+
+    /// Like `detect`, but trying the mappings first.
+    pub fn detect(&self, path: &Path, contents: &str) -> Option<Language> {
+        self.language(path).or_else(|| detect(path, contents))
+    }
+
+    /// Like `detect_file`, but trying the mappings first.
+    pub fn detect_file(&self, path: &Path) -> io::Result<Option<Language>> {
+        match self.language(path) {
+            Some(language) => Ok(Some(language)),
+            None => detect_file(path),
+        }
+    }
+}
+
+// Reads `#!/usr/bin/python3` or `#!/usr/bin/env -S node --flag` style lines
+fn shebang(contents: &str) -> Option<Language> {
+    let line = contents.lines().next()?.strip_prefix("#!")?;
+    let mut words = line.split_whitespace();
+    let mut interpreter = words.next()?.rsplit('/').next()?;
+    if interpreter == "env" {
+        interpreter = words.find(|word| !word.starts_with('-'))?;
+    }
+    // Drop version suffixes such as the "3" in "python3" or "3.11"
+    let interpreter = interpreter.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+    language_for_name(interpreter)
+}
+
+// Reads Vim (`vim: set ft=python:`) and Emacs (`-*- mode: python -*-`) modelines
+fn modeline(contents: &str) -> Option<Language> {
+    let lines: Vec<&str> = contents.lines().collect();
+    let head = MODELINE_LINES.min(lines.len());
+    let tail = lines.len().saturating_sub(MODELINE_LINES).max(head);
+    lines[..head]
+        .iter()
+        .chain(lines[tail..].iter())
+        .find_map(|line| emacs_modeline(line).or_else(|| vim_modeline(line)))
+}
+
+fn emacs_modeline(line: &str) -> Option<Language> {
+    let start = line.find("-*-")? + 3;
+    let end = start + line[start..].find("-*-")?;
+    let settings = &line[start..end];
+    if !settings.contains(':') {
+        // The short form only holds the mode, as in `-*- python -*-`
+        return language_for_name(settings);
+    }
+    settings.split(';').find_map(|setting| {
+        let (key, value) = setting.split_once(':')?;
+        if key.trim().eq_ignore_ascii_case("mode") {
+            language_for_name(value)
+        } else {
+            None
+        }
+    })
+}
+
+fn vim_modeline(line: &str) -> Option<Language> {
+    // The marker must start the line or follow whitespace, as in Vim itself
+    let start = ["vim:", "vi:", "ex:"]
+        .iter()
+        .flat_map(|marker| {
+            line.match_indices(marker)
+                .filter(|(index, _)| {
+                    line[..*index]
+                        .chars()
+                        .next_back()
+                        .is_none_or(char::is_whitespace)
+                })
+                .map(|(index, _)| index + marker.len())
+        })
+        .min()?;
+    line[start..]
+        .split(|c: char| c == ':' || c.is_whitespace())
+        .find_map(|option| {
+            let value = option
+                .strip_prefix("filetype=")
+                .or_else(|| option.strip_prefix("ft="))
+                .or_else(|| option.strip_prefix("syntax="))
+                .or_else(|| option.strip_prefix("syn="))?;
+            language_for_name(value)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_from_extension() {
+        assert_eq!(detect(Path::new("app/view.tsx"), ""), Some(Language::Tsx));
+        assert_eq!(detect(Path::new("README"), "plain text"), None);
+    }
+
+    #[test]
+    fn test_detect_from_shebang() {
+        let path = Path::new("bin/tool");
+        assert_eq!(
+            detect(path, "#!/usr/bin/env python3\nprint(1)\n"),
+            Some(Language::Python)
+        );
+        assert_eq!(
+            detect(path, "#!/usr/bin/env -S node --harmony\n"),
+            Some(Language::JavaScript)
+        );
+        assert_eq!(detect(path, "#!/usr/local/bin/php\n"), Some(Language::Php));
+        assert_eq!(detect(path, "#!/bin/sh\n"), None);
+    }
+
+    #[test]
+    fn test_detect_from_modelines() {
+        let path = Path::new("build.txt");
+        assert_eq!(
+            detect(path, "# -*- mode: python; coding: utf-8 -*-\n"),
+            Some(Language::Python)
+        );
+        assert_eq!(detect(path, "// -*- c++ -*-\n"), Some(Language::Cpp));
+        assert_eq!(
+            detect(path, "fn main() {}\n\n// vim: set ft=rust ts=4:\n"),
+            Some(Language::Rust)
+        );
+    }
+
+    #[test]
+    fn test_modeline_overrides_extension() {
+        assert_eq!(
+            detect(Path::new("script.js"), "// vim: ft=typescript\n"),
+            Some(Language::TypeScript)
+        );
+    }
+
+    #[test]
+    fn test_vim_marker_must_follow_whitespace() {
+        assert_eq!(detect(Path::new("a"), "let index: ft=rust\n"), None);
+    }
+
+    #[test]
+    fn test_mappings_take_priority() {
+        let mappings = Mappings::new(&[
+            ("*.inc".to_string(), Language::Php),
+            ("*.js".to_string(), Language::TypeScript),
+        ])
+        .unwrap();
+        assert_eq!(
+            mappings.detect(Path::new("lib/config.inc"), ""),
+            Some(Language::Php)
+        );
+        assert_eq!(
+            mappings.detect(Path::new("app.js"), "#!/usr/bin/env node\n"),
+            Some(Language::TypeScript)
+        );
+        assert_eq!(
+            mappings.detect(Path::new("main.rs"), ""),
+            Some(Language::Rust)
+        );
+        assert!(Mappings::new(&[("[".to_string(), Language::C)]).is_err());
+    }
+
+    #[test]
+    fn test_detect_file_reads_shebang() {
+        let path =
+            std::env::temp_dir().join(format!("plexity-detect-shebang-{}", std::process::id()));
+        std::fs::write(&path, "#!/usr/bin/python3\nprint(1)\n").unwrap();
+        assert_eq!(detect_file(&path).unwrap(), Some(Language::Python));
+        std::fs::remove_file(&path).unwrap();
+        assert!(detect_file(Path::new("no-such-file")).is_err());
+    }
+
+    #[test]
+    fn test_detect_file_reads_modelines_at_both_ends() {
+        let dir = std::env::temp_dir();
+        let path = dir.join(format!("plexity-detect-tail-{}.js", std::process::id()));
+        let body = "let x = 1;\n".repeat(300);
+        std::fs::write(&path, format!("{body}// vim: ft=typescript\n")).unwrap();
+        assert_eq!(detect_file(&path).unwrap(), Some(Language::TypeScript));
+        std::fs::write(&path, format!("// -*- mode: tsx -*-\n{body}")).unwrap();
+        assert_eq!(detect_file(&path).unwrap(), Some(Language::Tsx));
+        std::fs::write(&path, &body).unwrap();
+        assert_eq!(detect_file(&path).unwrap(), Some(Language::JavaScript));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
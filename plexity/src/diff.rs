@@ -0,0 +1,146 @@
+/*  A module for working out how the metrics of a file, and of each of its
+functions, changed between two versions of the file. Functions are matched
+by their qualified name, and only those that were added, or whose source
+changed, are kept.  */
+
+use crate::git::Status;
+use crate::language::Language;
+use crate::report::{Metrics, Report, Span};
+use serde::Serialize;
+
+/// How one changed file's metrics moved between two versions.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FileDelta {
+    #[serde(rename = "file")]
+    pub path: String,
+    /// The path of the older version, when the file was renamed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old_path: Option<String>,
+    pub status: Status,
+    pub language: Language,
+    /// The metrics of the older version, unless the file was added.
+    pub before: Option<Metrics>,
+    pub after: Metrics,
+    /// The functions that were added or modified.
+    pub functions: Vec<FunctionDelta>,
+}
+
+/// How one added or modified function's metrics moved between two versions.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FunctionDelta {
+    pub name: String,
+    /// Either `added` or `modified`.
+    pub status: Status,
+    /// Where the function is in the newer version.
+    pub span: Span,
+    /// The metrics of the older version, unless the function was added.
+    pub before: Option<Metrics>,
+    pub after: Metrics,
+}
+
+/// Lists the functions in `after` that are new, or whose source differs
+/// from the function of the same name in `before`. Each version is given
+/// as its source code and report, and `before` is `None` for added files.
+pub fn function_deltas(
+    before: Option<(&str, &Report)>,
+    after: (&str, &Report),
+) -> Vec<FunctionDelta> {
+    let (after_source, after_report) = after;
+    let mut unmatched: Vec<_> = match before {
+        Some((source, report)) => report
+            .functions
+            .iter()
+            .map(|function| (function, span_text(source, function.span)))
+            .collect(),
+        None => Vec::new(),
+    };
+
+    let mut deltas = Vec::new();
+    for function in &after_report.functions {
+        let text = span_text(after_source, function.span);
+        // Functions sharing a name (such as overloads) are matched in order
+        let matched = unmatched
+            .iter()
+            .position(|(old, _)| old.name == function.name)
+            .map(|index| unmatched.remove(index));
+        let (status, before) = match matched {
+            Some((_, old_text)) if old_text == text => continue,
+            Some((old, _)) => (Status::Modified, Some(old.metrics.clone())),
+            None => (Status::Added, None),
+        };
+        deltas.push(FunctionDelta {
+            name: function.name.clone(),
+            status,
+            span: function.span,
+            before,
+            after: function.metrics.clone(),
+        });
+    }
+    deltas
+}
+
+// The source code covered by `span`, whose columns count bytes
+fn span_text(source: &str, span: Span) -> &str {
+    let offset = |line: usize, column: usize| {
+        let line_start: usize = source
+            .split_inclusive('\n')
+            .take(line - 1)
+            .map(str::len)
+            .sum();
+        (line_start + column - 1).min(source.len())
+    };
+    let start = offset(span.start.line, span.start.column);
+    let end = offset(span.end.line, span.end.column);
+    source.get(start..end).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyze;
+
+    fn deltas(before: Option<&str>, after: &str) -> Vec<FunctionDelta> {
+        let before_report = before.map(|source| analyze(source, Language::Python).unwrap());
+        let after_report = analyze(after, Language::Python).unwrap();
+        function_deltas(before.zip(before_report.as_ref()), (after, &after_report))
+    }
+
+    #[test]
+    fn test_only_added_and_modified_functions() {
+        let before = "def same():\n    pass\n\ndef changed(a):\n    return a\n";
+        let after = "# A new comment\ndef same():\n    pass\n\ndef changed(a):\n    if a:\n        return a\n\ndef new():\n    pass\n";
+        let found = deltas(Some(before), after);
+        let names: Vec<(&str, Status)> =
+            found.iter().map(|d| (d.name.as_str(), d.status)).collect();
+        assert_eq!(
+            names,
+            vec![("changed", Status::Modified), ("new", Status::Added)]
+        );
+        let changed = &found[0];
+        assert_eq!(
+            changed.before.as_ref().unwrap().cyclomatic_complexity,
+            Some(1)
+        );
+        assert_eq!(changed.after.cyclomatic_complexity, Some(2));
+        assert_eq!(changed.span.start.line, 5);
+    }
+
+    #[test]
+    fn test_every_function_of_an_added_file_is_added() {
+        let found = deltas(None, "def a():\n    pass\n\ndef b():\n    pass\n");
+        assert_eq!(found.len(), 2);
+        assert!(found
+            .iter()
+            .all(|delta| delta.status == Status::Added && delta.before.is_none()));
+    }
+
+    #[test]
+    fn test_span_text() {
+        let source = "ab\ncdé\nf";
+        let span = Span {
+            start: crate::report::Position { line: 2, column: 2 },
+            end: crate::report::Position { line: 3, column: 2 },
+        };
+        assert_eq!(span_text(source, span), "dé\nf");
+    }
+}
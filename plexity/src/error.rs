@@ -0,0 +1,133 @@
+/*  A module for the errors that can occur while analysing source code.  */
+
+use crate::language::Language;
+use std::error;
+use std::fmt;
+use std::path::PathBuf;
+
+/// The ways in which analysing a piece of source code can fail.
+#[derive(Debug)]
+pub enum Error {
+    /// tree-sitter was unable to produce a syntax tree for the source code.
+    Parse,
+    /// A bundled grammar was built for an incompatible version of tree-sitter.
+    Grammar(tree_sitter::LanguageError),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Parse => write!(f, "unable to build a syntax tree from the source code"),
+            Error::Grammar(err) => write!(f, "unable to load the grammar: {err}"),
+        }
+    }
+}
+
+impl error::Error for Error {}
+
+impl From<tree_sitter::LanguageError> for Error {
+    fn from(err: tree_sitter::LanguageError) -> Error {
+        Error::Grammar(err)
+    }
+}
+
+/// A language name that does not match any supported language.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownLanguage {
+    /// The name as it was given.
+    pub name: String,
+    /// The supported languages whose names are close to `name`.
+    pub suggestions: Vec<Language>,
+}
+
+impl UnknownLanguage {
+    pub fn new(name: &str) -> UnknownLanguage {
+        let wanted = name.trim().to_lowercase();
+        // Allow roughly one typo for every three characters
+        let tolerance = (wanted.chars().count() / 3).max(1);
+        let suggestions = Language::ALL
+            .iter()
+            .filter(|language| {
+                let known = language.name();
+                (wanted.len() > 1 && known.starts_with(&wanted))
+                    || edit_distance(&wanted, known) <= tolerance
+            })
+            .copied()
+            .collect();
+
+        UnknownLanguage {
+            name: name.to_string(),
+            suggestions,
+        }
+    }
+}
+
+impl fmt::Display for UnknownLanguage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "\"{}\" is not a supported programming language",
+            self.name
+        )?;
+        if !self.suggestions.is_empty() {
+            let names: Vec<&str> = self.suggestions.iter().map(|l| l.name()).collect();
+            write!(f, " (did you mean {}?)", names.join(" or "))?;
+        }
+        let supported: Vec<&str> = Language::ALL.iter().map(|l| l.name()).collect();
+        write!(f, "; the supported languages are {}", supported.join(", "))
+    }
+}
+
+impl error::Error for UnknownLanguage {}
+
+/// A configuration file that could not be read or understood.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigError {
+    pub path: PathBuf,
+    pub message: String,
+}
+
+impl ConfigError {
+    pub fn new(path: impl Into<PathBuf>, message: impl fmt::Display) -> ConfigError {
+        ConfigError {
+            path: path.into(),
+            message: message.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path.display(), self.message)
+    }
+}
+
+impl error::Error for ConfigError {}
+
+// The Levenshtein distance between two names
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("rust", "rust"), 0);
+        assert_eq!(edit_distance("rsut", "rust"), 2);
+        assert_eq!(edit_distance("jav", "java"), 1);
+        assert_eq!(edit_distance("", "toml"), 4);
+    }
+}
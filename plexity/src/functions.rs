@@ -0,0 +1,209 @@
+/*  A module for discovering the functions and methods defined in a
+syntax tree, so that each can be measured on its own.  */
+
+use crate::language::Language;
+use tree_sitter::Node;
+
+/// A function or method found in a syntax tree.
+#[derive(Debug, Clone)]
+pub struct Function<'tree> {
+    /// The function's name, qualified by any enclosing classes or functions.
+    pub name: String,
+    pub node: Node<'tree>,
+}
+
+// The node kinds that define a function, method or constructor
+pub(crate) fn function_kinds(language: Language) -> &'static [&'static str] {
+    match language {
+        Language::C | Language::Cpp => &["function_definition"],
+        Language::Java => &["method_declaration", "constructor_declaration"],
+        Language::JavaScript | Language::TypeScript | Language::Tsx => &[
+            "function_declaration",
+            "generator_function_declaration",
+            "method_definition",
+            "function",
+            "generator_function",
+            "arrow_function",
+        ],
+        Language::Php => &["function_definition", "method_declaration"],
+        Language::Python => &["function_definition"],
+        Language::Rust => &["function_item"],
+        _ => &[],
+    }
+}
+
+// The node kinds that group functions together and qualify their names
+fn container_kinds(language: Language) -> &'static [&'static str] {
+    match language {
+        Language::Cpp => &[
+            "class_specifier",
+            "struct_specifier",
+            "namespace_definition",
+        ],
+        Language::Java => &[
+            "class_declaration",
+            "interface_declaration",
+            "enum_declaration",
+            "record_declaration",
+        ],
+        Language::JavaScript | Language::TypeScript | Language::Tsx => {
+            &["class_declaration", "class"]
+        }
+        Language::Php => &[
+            "class_declaration",
+            "interface_declaration",
+            "trait_declaration",
+        ],
+        Language::Python => &["class_definition"],
+        Language::Rust => &["impl_item", "trait_item", "mod_item"],
+        _ => &[],
+    }
+}
+
+/// Finds every function in the tree below `root`, in source order.
+pub fn discover<'tree>(
+    root: Node<'tree>,
+    source: &str,
+    language: Language,
+) -> Vec<Function<'tree>> {
+    let mut functions = Vec::new();
+    let mut scope = Vec::new();
+    visit(root, source, language, &mut scope, &mut functions);
+    functions
+}
+
+fn visit<'tree>(
+    node: Node<'tree>,
+    source: &str,
+    language: Language,
+    scope: &mut Vec<String>,
+    functions: &mut Vec<Function<'tree>>,
+) {
+    for i in 0..node.named_child_count() {
+        let child = node.named_child(i).unwrap();
+        let kind = child.kind();
+
+        if function_kinds(language).contains(&kind) {
+            scope.push(function_name(child, source));
+            functions.push(Function {
+                name: scope.join("."),
+                node: child,
+            });
+            visit(child, source, language, scope, functions);
+            scope.pop();
+        } else if container_kinds(language).contains(&kind) {
+            scope.push(container_name(child, source));
+            visit(child, source, language, scope, functions);
+            scope.pop();
+        } else {
+            visit(child, source, language, scope, functions);
+        }
+    }
+}
+
+fn text(node: Node, source: &str) -> String {
+    node.utf8_text(source.as_bytes())
+        .unwrap_or_default()
+        .to_string()
+}
+
+pub(crate) fn function_name(node: Node, source: &str) -> String {
+    if let Some(name) = node.child_by_field_name("name") {
+        return text(name, source);
+    }
+
+    // C and C++ keep the name inside a (possibly nested) declarator
+    if let Some(declarator) = node.child_by_field_name("declarator") {
+        return declarator_name(declarator, source);
+    }
+
+    // Anonymous JavaScript functions take the name they are assigned to
+    if let Some(parent) = node.parent() {
+        let target = match parent.kind() {
+            "variable_declarator" => parent.child_by_field_name("name"),
+            "assignment_expression" => parent.child_by_field_name("left"),
+            "pair" => parent.child_by_field_name("key"),
+            _ => None,
+        };
+        if let Some(target) = target {
+            return text(target, source);
+        }
+    }
+
+    "<anonymous>".to_string()
+}
+
+fn declarator_name(node: Node, source: &str) -> String {
+    if node.kind() == "function_declarator" {
+        if let Some(declarator) = node.child_by_field_name("declarator") {
+            return text(declarator, source);
+        }
+    }
+    match node
+        .child_by_field_name("declarator")
+        .or_else(|| node.named_child(0))
+    {
+        Some(inner) => declarator_name(inner, source),
+        None => text(node, source),
+    }
+}
+
+fn container_name(node: Node, source: &str) -> String {
+    // Rust's impl blocks are named after the type they implement
+    let name = node
+        .child_by_field_name("name")
+        .or_else(|| node.child_by_field_name("type"));
+    match name {
+        Some(name) => text(name, source),
+        None => "<anonymous>".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::language::select_parser;
+
+    fn names(source: &str, language: Language) -> Vec<String> {
+        let tree = select_parser(language)
+            .unwrap()
+            .parse(source, None)
+            .unwrap();
+        discover(tree.root_node(), source, language)
+            .into_iter()
+            .map(|function| function.name)
+            .collect()
+    }
+
+    #[test]
+    fn test_discover_python_methods() {
+        let source = "def top():\n    def inner():\n        pass\n\nclass Greeter:\n    def greet(self):\n        pass\n";
+        assert_eq!(
+            names(source, Language::Python),
+            vec!["top", "top.inner", "Greeter.greet"]
+        );
+    }
+
+    #[test]
+    fn test_discover_rust_impl() {
+        let source = "fn main() {}\nimpl Config { fn build() {} }\n";
+        assert_eq!(names(source, Language::Rust), vec!["main", "Config.build"]);
+    }
+
+    #[test]
+    fn test_discover_c_declarators() {
+        let source = "int fib(int n) { return n; }\nchar *name(void) { return 0; }\n";
+        assert_eq!(names(source, Language::C), vec!["fib", "name"]);
+    }
+
+    #[test]
+    fn test_discover_javascript_assigned_arrow() {
+        let source = "const fib = (n) => n;\nclass A { run() {} }\n";
+        assert_eq!(names(source, Language::JavaScript), vec!["fib", "A.run"]);
+    }
+
+    #[test]
+    fn test_discover_markup_has_no_functions() {
+        assert!(names("{\"a\": 1}", Language::Json).is_empty());
+    }
+}
@@ -0,0 +1,242 @@
+/*  A module for reading the files that changed between two revisions of a
+local git repository, and the commits that touched a path, by running the
+`git` command itself.  */
+
+use serde::Serialize;
+use std::io;
+use std::path::Path;
+use std::process::Command;
+
+/// How a file, or a function in it, changed between two revisions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    Added,
+    Modified,
+    Deleted,
+    Renamed,
+}
+
+impl Status {
+    pub fn name(self) -> &'static str {
+        match self {
+            Status::Added => "added",
+            Status::Modified => "modified",
+            Status::Deleted => "deleted",
+            Status::Renamed => "renamed",
+        }
+    }
+}
+
+/// A file that changed between two revisions. Paths are relative to the
+/// root of the repository.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change {
+    pub status: Status,
+    /// The path in the newer revision, or in the older one for deleted files.
+    pub path: String,
+    /// The path in the older revision, for renamed files.
+    pub old_path: Option<String>,
+}
+
+impl Change {
+    fn new(status: Status, path: String, old_path: Option<String>) -> Change {
+        Change {
+            status,
+            path,
+            old_path,
+        }
+    }
+}
+
+/// Lists the files that changed from revision `base` to revision `head` in
+/// the repository holding `directory`.
+pub fn changed_files(directory: &Path, base: &str, head: &str) -> io::Result<Vec<Change>> {
+    check_revision(base)?;
+    check_revision(head)?;
+    let output = run(
+        directory,
+        &[
+            "diff",
+            "--name-status",
+            "-z",
+            "--find-renames",
+            base,
+            head,
+            "--",
+        ],
+    )?;
+    parse_name_status(&String::from_utf8_lossy(&output))
+}
+
+/// Reads the file at `path`, relative to the root of the repository, as it
+/// was in `revision`.
+pub fn show(directory: &Path, revision: &str, path: &str) -> io::Result<String> {
+    check_revision(revision)?;
+    let output = run(directory, &["show", &format!("{revision}:{path}")])?;
+    String::from_utf8(output).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+/// A commit in the history of a path.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Commit {
+    pub hash: String,
+    /// The committer date, in ISO 8601 format.
+    pub date: String,
+    pub subject: String,
+}
+
+/// Lists the commits that touched `path`, oldest first.
+pub fn log(directory: &Path, path: &Path) -> io::Result<Vec<Commit>> {
+    let path = path.to_string_lossy();
+    let output = run(
+        directory,
+        &[
+            "log",
+            "--reverse",
+            "--format=%H%x00%cI%x00%s%x00",
+            "--",
+            &path,
+        ],
+    )?;
+    parse_log(&String::from_utf8_lossy(&output))
+}
+
+/// Lists the files at or below `path` in `revision`, relative to the root
+/// of the repository.
+pub fn files(directory: &Path, revision: &str, path: &Path) -> io::Result<Vec<String>> {
+    check_revision(revision)?;
+    let path = path.to_string_lossy();
+    let output = run(
+        directory,
+        &[
+            "ls-tree",
+            "-r",
+            "-z",
+            "--name-only",
+            "--full-name",
+            revision,
+            "--",
+            &path,
+        ],
+    )?;
+    Ok(String::from_utf8_lossy(&output)
+        .split('\0')
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+// Reads the hash, date and subject of each commit, each followed by a NUL
+fn parse_log(output: &str) -> io::Result<Vec<Commit>> {
+    let fields: Vec<&str> = output.split('\0').map(str::trim).collect();
+    fields
+        .chunks(3)
+        .filter(|chunk| chunk.len() == 3)
+        .map(|chunk| match chunk {
+            [hash, date, subject] if !hash.is_empty() => Ok(Commit {
+                hash: hash.to_string(),
+                date: date.to_string(),
+                subject: subject.to_string(),
+            }),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "unexpected output from git log",
+            )),
+        })
+        .collect()
+}
+
+// Revisions are passed straight to git, so they must not look like options
+fn check_revision(revision: &str) -> io::Result<()> {
+    if revision.is_empty() || revision.starts_with('-') {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("\"{revision}\" is not a revision"),
+        ));
+    }
+    Ok(())
+}
+
+fn run(directory: &Path, args: &[&str]) -> io::Result<Vec<u8>> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(directory)
+        .args(args)
+        .output()
+        .map_err(|err| io::Error::new(err.kind(), format!("unable to run git: {err}")))?;
+    if !output.status.success() {
+        let message = String::from_utf8_lossy(&output.stderr);
+        return Err(io::Error::other(message.trim().to_string()));
+    }
+    Ok(output.stdout)
+}
+
+// Reads the NUL-separated output of `git diff --name-status -z`, where
+// renames and copies are followed by both paths and everything else by one
+fn parse_name_status(output: &str) -> io::Result<Vec<Change>> {
+    let invalid = || {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            "unexpected output from git diff",
+        )
+    };
+    let mut fields = output.split('\0').filter(|field| !field.is_empty());
+    let mut changes = Vec::new();
+    while let Some(code) = fields.next() {
+        let mut path = || fields.next().map(str::to_string).ok_or_else(invalid);
+        let change = match code.as_bytes().first() {
+            Some(b'A') => Change::new(Status::Added, path()?, None),
+            Some(b'M' | b'T') => Change::new(Status::Modified, path()?, None),
+            Some(b'D') => Change::new(Status::Deleted, path()?, None),
+            Some(b'R') => {
+                let old_path = path()?;
+                Change::new(Status::Renamed, path()?, Some(old_path))
+            }
+            // A copy leaves the original alone, so only the copy is new
+            Some(b'C') => {
+                path()?;
+                Change::new(Status::Added, path()?, None)
+            }
+            _ => return Err(invalid()),
+        };
+        changes.push(change);
+    }
+    Ok(changes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_name_status() {
+        let output = "M\0src/a.py\0A\0src/b.rs\0R087\0old.py\0new.py\0D\0gone.c\0";
+        let changes = parse_name_status(output).unwrap();
+        assert_eq!(changes.len(), 4);
+        assert_eq!(changes[0].status, Status::Modified);
+        assert_eq!(changes[1].path, "src/b.rs");
+        assert_eq!(changes[2].old_path.as_deref(), Some("old.py"));
+        assert_eq!(changes[2].path, "new.py");
+        assert_eq!(changes[3].status, Status::Deleted);
+        assert!(parse_name_status("M\0").is_err());
+        assert!(parse_name_status("X\0a\0").is_err());
+    }
+
+    #[test]
+    fn test_parse_log() {
+        let output = "abc\x002024-01-02T03:04:05+00:00\0Fix things\0\ndef\x002024-02-01T00:00:00+00:00\0More\0\n";
+        let commits = parse_log(output).unwrap();
+        assert_eq!(commits.len(), 2);
+        assert_eq!(commits[0].hash, "abc");
+        assert_eq!(commits[1].date, "2024-02-01T00:00:00+00:00");
+        assert_eq!(commits[1].subject, "More");
+        assert!(parse_log("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_revisions_cannot_be_options() {
+        let err = changed_files(Path::new("."), "--output=x", "HEAD").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+}
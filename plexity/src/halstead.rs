@@ -0,0 +1,207 @@
+/*  A module for calculating Halstead's software science metrics from
+the leaves of a syntax tree, which are classified as either operators
+(keywords, punctuation and operator symbols) or operands (identifiers
+and literals).  */
+
+use crate::language::Language;
+use crate::suppress::Suppressions;
+use serde::Serialize;
+use std::collections::HashSet;
+use tree_sitter::Node;
+
+/// Halstead's metrics for a syntax tree or one of its subtrees.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Halstead {
+    /// Number of distinct operators (n1).
+    pub distinct_operators: usize,
+    /// Number of distinct operands (n2).
+    pub distinct_operands: usize,
+    /// Total number of operators (N1).
+    pub total_operators: usize,
+    /// Total number of operands (N2).
+    pub total_operands: usize,
+    /// n1 + n2
+    pub vocabulary: usize,
+    /// N1 + N2
+    pub length: usize,
+    /// length * log2(vocabulary)
+    pub volume: f64,
+    /// (n1 / 2) * (N2 / n2)
+    pub difficulty: f64,
+    /// difficulty * volume
+    pub effort: f64,
+    /// volume / 3000
+    pub estimated_bugs: f64,
+}
+
+// The literals that are made up of several leaves but count as one operand
+fn literal_kinds(language: Language) -> &'static [&'static str] {
+    match language {
+        Language::C => &["string_literal", "char_literal", "system_lib_string"],
+        Language::Cpp => &[
+            "string_literal",
+            "raw_string_literal",
+            "char_literal",
+            "system_lib_string",
+        ],
+        Language::Java => &["string_literal", "character_literal"],
+        Language::JavaScript | Language::TypeScript | Language::Tsx => &["string", "regex"],
+        Language::Php => &["string", "encapsed_string", "heredoc", "nowdoc"],
+        Language::Python => &["string"],
+        Language::Rust => &["string_literal", "raw_string_literal", "char_literal"],
+        _ => &[],
+    }
+}
+
+// Closing delimiters belong to the same operator as their opening pair
+const CLOSING_DELIMITERS: [&str; 3] = [")", "]", "}"];
+
+/// Whether Halstead's metrics can be calculated for `language`.
+pub fn supported(language: Language) -> bool {
+    !literal_kinds(language).is_empty()
+}
+
+/// Calculates Halstead's metrics for the leaves below `node`, skipping the
+/// subtrees covered by `suppressions`.
+pub fn halstead(
+    node: Node,
+    source: &str,
+    language: Language,
+    suppressions: &Suppressions,
+) -> Option<Halstead> {
+    if !supported(language) {
+        return None;
+    }
+    let mut counts = Counts::default();
+    classify(node, source, language, suppressions, &mut counts);
+    Some(counts.into_halstead())
+}
+
+#[derive(Default)]
+struct Counts<'a> {
+    operators: HashSet<&'static str>,
+    operands: HashSet<&'a str>,
+    total_operators: usize,
+    total_operands: usize,
+}
+
+impl Counts<'_> {
+    fn into_halstead(self) -> Halstead {
+        let distinct_operators = self.operators.len();
+        let distinct_operands = self.operands.len();
+        let vocabulary = distinct_operators + distinct_operands;
+        let length = self.total_operators + self.total_operands;
+
+        let volume = if vocabulary > 0 {
+            length as f64 * (vocabulary as f64).log2()
+        } else {
+            0.0
+        };
+        let difficulty = if distinct_operands > 0 {
+            (distinct_operators as f64 / 2.0)
+                * (self.total_operands as f64 / distinct_operands as f64)
+        } else {
+            0.0
+        };
+
+        Halstead {
+            distinct_operators,
+            distinct_operands,
+            total_operators: self.total_operators,
+            total_operands: self.total_operands,
+            vocabulary,
+            length,
+            volume,
+            difficulty,
+            effort: difficulty * volume,
+            estimated_bugs: volume / 3000.0,
+        }
+    }
+}
+
+fn classify<'a>(
+    node: Node,
+    source: &'a str,
+    language: Language,
+    suppressions: &Suppressions,
+    counts: &mut Counts<'a>,
+) {
+    for i in 0..node.child_count() {
+        let child = node.child(i).unwrap();
+        if suppressions.covers(child) {
+            continue;
+        }
+        let kind = child.kind();
+
+        if child.is_extra() && kind.contains("comment") {
+            continue;
+        }
+
+        if literal_kinds(language).contains(&kind) || (child.child_count() == 0 && child.is_named())
+        {
+            let text = child.utf8_text(source.as_bytes()).unwrap_or_default();
+            counts.operands.insert(text);
+            counts.total_operands += 1;
+        } else if child.child_count() == 0 {
+            if kind.is_empty() || CLOSING_DELIMITERS.contains(&kind) {
+                continue;
+            }
+            counts.operators.insert(kind);
+            counts.total_operators += 1;
+        } else {
+            classify(child, source, language, suppressions, counts);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::language::select_parser;
+
+    fn measure(source: &str, language: Language) -> Option<Halstead> {
+        let tree = select_parser(language)
+            .unwrap()
+            .parse(source, None)
+            .unwrap();
+        halstead(tree.root_node(), source, language, &Suppressions::default())
+    }
+
+    #[test]
+    fn test_halstead_python_counts() {
+        // Operators: = (twice) and +; operands: x (twice), y, 1 and "two words"
+        let halstead = measure(
+            "x = 1\ny = x + \"two words\"  # comment\n",
+            Language::Python,
+        )
+        .unwrap();
+        assert_eq!(halstead.distinct_operators, 2);
+        assert_eq!(halstead.total_operators, 3);
+        assert_eq!(halstead.distinct_operands, 4);
+        assert_eq!(halstead.total_operands, 5);
+        assert_eq!(halstead.vocabulary, 6);
+        assert_eq!(halstead.length, 8);
+        assert!((halstead.volume - 8.0 * 6f64.log2()).abs() < 1e-9);
+        assert!((halstead.difficulty - 1.25).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_halstead_pairs_delimiters() {
+        // Operators: fn, ( and {; operands: f
+        let halstead = measure("fn f() {}", Language::Rust).unwrap();
+        assert_eq!(halstead.total_operators, 3);
+        assert_eq!(halstead.total_operands, 1);
+    }
+
+    #[test]
+    fn test_halstead_empty_source() {
+        let halstead = measure("", Language::Java).unwrap();
+        assert_eq!(halstead.volume, 0.0);
+        assert_eq!(halstead.difficulty, 0.0);
+    }
+
+    #[test]
+    fn test_halstead_unsupported_language() {
+        assert_eq!(measure("{}", Language::Json), None);
+    }
+}
@@ -0,0 +1,101 @@
+/*  A module for working out how deeply nested each line of a file is, so
+that its source can be shaded by depth.
+
+A line is as deep as the deepest token on it. Tokens are the leaves of the
+syntax tree, so every node above them is shallower and only the leaves
+need to be looked at. Lines holding no token, such as blank lines or the
+lines of suppressed code, have a depth of zero.  */
+
+use crate::hotspots::{self, Hotspot};
+use crate::report::NodeRecord;
+use tree_sitter::Node;
+
+/// The most deeply nested regions kept with each heatmap.
+pub const HOTSPOT_COUNT: usize = 10;
+
+/// The source code of a file with the depth of each of its lines.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Heatmap {
+    pub source: String,
+    /// The depth of the deepest token on each line, in order.
+    pub line_depths: Vec<usize>,
+    /// The most deeply nested regions of the file, deepest first.
+    pub hotspots: Vec<Hotspot>,
+}
+
+/// Gathers line depths from the nodes visited while traversing a tree,
+/// for use as (part of) the callback to `analyze_with`.
+#[derive(Debug, Default)]
+pub struct Collector {
+    line_depths: Vec<usize>,
+    hotspots: hotspots::Collector,
+}
+
+impl Collector {
+    /// Records the depth of the visited `node` when it is a token.
+    pub fn visit(&mut self, node: Node, record: &NodeRecord, source: &str) {
+        self.hotspots.visit(node, record, source);
+        if node.child_count() > 0 {
+            return;
+        }
+        let start = record.span.start.line;
+        let mut end = record.span.end.line;
+        // A token that takes in a line break ends at the start of the next line
+        if end > start && record.span.end.column == 1 {
+            end -= 1;
+        }
+        if self.line_depths.len() < end {
+            self.line_depths.resize(end, 0);
+        }
+        for depth in &mut self.line_depths[start - 1..end] {
+            *depth = (*depth).max(record.depth);
+        }
+    }
+
+    /// Pairs the line depths with the source code they were measured from.
+    pub fn finish(self, source: &str) -> Heatmap {
+        let mut line_depths = self.line_depths;
+        line_depths.resize(source.lines().count(), 0);
+        Heatmap {
+            source: source.to_string(),
+            line_depths,
+            hotspots: self.hotspots.finish(source, HOTSPOT_COUNT),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::language::Language;
+
+    fn heatmap(source: &str, language: Language) -> Heatmap {
+        let mut collector = Collector::default();
+        crate::analyze_with(source, language, |node, record| {
+            collector.visit(node, record, source)
+        })
+        .unwrap();
+        collector.finish(source)
+    }
+
+    #[test]
+    fn test_lines_take_the_depth_of_their_deepest_token() {
+        let source = "def f(a):\n\n    if a:\n        return g(h(a))\n";
+        let found = heatmap(source, Language::Python);
+        assert_eq!(found.line_depths.len(), 4);
+        assert_eq!(found.line_depths[1], 0);
+        assert!(found.line_depths[0] > 0);
+        assert!(found.line_depths[3] > found.line_depths[2]);
+        let report = crate::analyze(source, Language::Python).unwrap();
+        assert_eq!(found.line_depths[3], report.metrics.maximum_depth);
+        assert_eq!(found.hotspots[0].span.start.line, 4);
+    }
+
+    #[test]
+    fn test_tokens_spanning_lines() {
+        let source = "x = \"\"\"a\nb\"\"\"\ny = 1\n";
+        let found = heatmap(source, Language::Python);
+        assert!(found.line_depths[1] > 0);
+        assert_eq!(found.line_depths.len(), 3);
+    }
+}
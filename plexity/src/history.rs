@@ -0,0 +1,132 @@
+/*  A module for following how the metrics of a file or directory moved
+over its git history. Each sampled commit is rolled up into a single row of
+a time series, so that the trend can be plotted or compared over time.  */
+
+use crate::git::Commit;
+use crate::project::FileReport;
+use serde::Serialize;
+
+/// The metrics of a path at one commit.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Sample {
+    pub commit: String,
+    /// The committer date, in ISO 8601 format.
+    pub date: String,
+    pub subject: String,
+    /// The number of source files analysed at the commit.
+    pub file_count: usize,
+    pub node_count: usize,
+    /// The deepest node found in any file.
+    pub maximum_depth: usize,
+    /// Combined depth divided by the number of nodes across all files.
+    pub average_depth: f64,
+    /// The sum over the files for which it could be calculated.
+    pub cyclomatic_complexity: Option<usize>,
+}
+
+impl Sample {
+    /// Rolls the reports for the files at `commit` up into a sample.
+    pub fn new(commit: &Commit, files: &[FileReport]) -> Sample {
+        let node_count: usize = files.iter().map(|f| f.report.metrics.node_count).sum();
+        let combined_depth: usize = files.iter().map(|f| f.report.metrics.combined_depth).sum();
+        let cyclomatic: Vec<usize> = files
+            .iter()
+            .filter_map(|f| f.report.metrics.cyclomatic_complexity)
+            .collect();
+        Sample {
+            commit: commit.hash.clone(),
+            date: commit.date.clone(),
+            subject: commit.subject.clone(),
+            file_count: files.len(),
+            node_count,
+            maximum_depth: files
+                .iter()
+                .map(|f| f.report.metrics.maximum_depth)
+                .max()
+                .unwrap_or(0),
+            average_depth: if node_count > 0 {
+                combined_depth as f64 / node_count as f64
+            } else {
+                0.0
+            },
+            cyclomatic_complexity: (!cyclomatic.is_empty()).then(|| cyclomatic.iter().sum()),
+        }
+    }
+}
+
+/// Picks the commits to measure from a history listed oldest first: every
+/// `every`th commit counting back from the newest, which is always kept,
+/// and then only the newest `limit` of those when a limit is given.
+pub fn sample_commits(commits: Vec<Commit>, every: usize, limit: Option<usize>) -> Vec<Commit> {
+    let every = every.max(1);
+    let newest = commits.len().saturating_sub(1);
+    let mut sampled: Vec<Commit> = commits
+        .into_iter()
+        .enumerate()
+        .filter(|(index, _)| (newest - index).is_multiple_of(every))
+        .map(|(_, commit)| commit)
+        .collect();
+    if let Some(limit) = limit {
+        sampled.drain(..sampled.len().saturating_sub(limit));
+    }
+    sampled
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::language::Language;
+    use crate::project::file_report;
+
+    fn commit(hash: &str) -> Commit {
+        Commit {
+            hash: hash.to_string(),
+            date: "2024-01-01T00:00:00+00:00".to_string(),
+            subject: format!("Commit {hash}"),
+        }
+    }
+
+    #[test]
+    fn test_sample_commits() {
+        let commits: Vec<Commit> = ["a", "b", "c", "d", "e"].map(commit).to_vec();
+        let hashes = |commits: Vec<Commit>| -> Vec<String> {
+            commits.into_iter().map(|commit| commit.hash).collect()
+        };
+        assert_eq!(
+            hashes(sample_commits(commits.clone(), 1, None)),
+            ["a", "b", "c", "d", "e"]
+        );
+        assert_eq!(
+            hashes(sample_commits(commits.clone(), 2, None)),
+            ["a", "c", "e"]
+        );
+        assert_eq!(hashes(sample_commits(commits.clone(), 3, Some(1))), ["e"]);
+        assert_eq!(hashes(sample_commits(commits, 0, Some(2))), ["d", "e"]);
+        assert!(sample_commits(Vec::new(), 2, None).is_empty());
+    }
+
+    #[test]
+    fn test_sample_rolls_up_files() {
+        let files = [
+            file_report(
+                "a.py",
+                "def f(a):\n    if a:\n        return 1\n",
+                Language::Python,
+            ),
+            file_report("b.json", "{\"a\": [1, 2]}", Language::Json),
+        ];
+        let sample = Sample::new(&commit("abc"), &files);
+        let python = &files[0].report.metrics;
+        let json = &files[1].report.metrics;
+        assert_eq!(sample.commit, "abc");
+        assert_eq!(sample.file_count, 2);
+        assert_eq!(sample.node_count, python.node_count + json.node_count);
+        assert_eq!(
+            sample.maximum_depth,
+            python.maximum_depth.max(json.maximum_depth)
+        );
+        // JSON has no cyclomatic complexity, so only the Python file counts
+        assert_eq!(sample.cyclomatic_complexity, Some(2));
+        assert_eq!(Sample::new(&commit("abc"), &[]).cyclomatic_complexity, None);
+    }
+}
@@ -0,0 +1,202 @@
+/*  A module for pointing out the most deeply nested regions of a file.
+
+Each node is attributed to the line-level construct it belongs to: the
+nearest named node holding it that starts a line of code, such as a
+statement or an argument on a line of its own. A hotspot is one of those
+constructs, ranked by the deepest node attributed to it, so that the
+report points at the lines where the nesting actually happens.  */
+
+use crate::report::{NodeRecord, Span};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::ops::Range;
+use tree_sitter::Node;
+
+/// The number of hotspots listed when no count is given.
+pub const DEFAULT_HOTSPOT_COUNT: usize = 5;
+
+// The most lines of source code shown for each hotspot
+const SNIPPET_LINES: usize = 3;
+
+/// A deeply nested region of a file.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Hotspot {
+    /// The kind of the construct that holds the nesting.
+    pub kind: String,
+    pub span: Span,
+    /// The depth of the deepest node in the region.
+    pub depth: usize,
+    /// The first few lines of the region's source code.
+    pub snippet: String,
+}
+
+/// Gathers hotspots from the nodes visited while traversing a tree, for
+/// use as (part of) the callback to `analyze_with`.
+#[derive(Debug, Default)]
+pub struct Collector {
+    regions: HashMap<(usize, usize), Region>,
+}
+
+#[derive(Debug)]
+struct Region {
+    kind: String,
+    span: Span,
+    bytes: Range<usize>,
+    depth: usize,
+}
+
+impl Collector {
+    /// Attributes the visited `node` to its line-level construct.
+    pub fn visit(&mut self, node: Node, record: &NodeRecord, source: &str) {
+        let region = line_construct(node, source);
+        let bytes = region.byte_range();
+        self.regions
+            .entry((bytes.start, bytes.end))
+            .and_modify(|existing| {
+                // Nodes sharing a range are visited outermost first, so the
+                // innermost one names the region
+                existing.kind = region.kind().to_string();
+                existing.depth = existing.depth.max(record.depth);
+            })
+            .or_insert_with(|| Region {
+                kind: region.kind().to_string(),
+                span: region.range().into(),
+                bytes,
+                depth: record.depth,
+            });
+    }
+
+    /// Ranks the regions by depth and returns up to `count` of them that do
+    /// not overlap, deepest first.
+    pub fn finish(self, source: &str, count: usize) -> Vec<Hotspot> {
+        let mut regions: Vec<Region> = self.regions.into_values().collect();
+        regions.sort_by(|a, b| {
+            b.depth
+                .cmp(&a.depth)
+                .then(a.bytes.start.cmp(&b.bytes.start))
+        });
+
+        let mut chosen: Vec<Region> = Vec::new();
+        for region in regions {
+            if chosen.len() == count {
+                break;
+            }
+            let overlaps = chosen.iter().any(|other| {
+                region.bytes.start < other.bytes.end && other.bytes.start < region.bytes.end
+            });
+            if !overlaps {
+                chosen.push(region);
+            }
+        }
+
+        chosen
+            .into_iter()
+            .map(|region| Hotspot {
+                snippet: snippet(&source[region.bytes.clone()]),
+                kind: region.kind,
+                span: region.span,
+                depth: region.depth,
+            })
+            .collect()
+    }
+}
+
+// The construct that `node` belongs to: the nearest named node (or the
+// top-level node holding it) that is the first thing on its line, widened
+// to take in the rest of an expression that starts at the same place
+fn line_construct<'tree>(node: Node<'tree>, source: &str) -> Node<'tree> {
+    let mut current = node;
+    loop {
+        if current.is_named() && current.child_count() > 0 && starts_line(current, source) {
+            break;
+        }
+        match current.parent() {
+            Some(parent) if parent.parent().is_some() => current = parent,
+            _ => return current,
+        }
+    }
+
+    while let Some(parent) = current.parent() {
+        let widens = parent.parent().is_some()
+            && parent.start_byte() == current.start_byte()
+            && parent.end_position().row == current.end_position().row
+            && !holds_lines(parent, source);
+        if !widens {
+            break;
+        }
+        current = parent;
+    }
+    current
+}
+
+// Whether every named child of `node` starts a line, as the statements in
+// a block do
+fn holds_lines(node: Node, source: &str) -> bool {
+    (0..node.named_child_count())
+        .filter_map(|i| node.named_child(i))
+        .all(|child| starts_line(child, source))
+}
+
+fn starts_line(node: Node, source: &str) -> bool {
+    let start = node.start_byte();
+    let line_start = source[..start].rfind('\n').map_or(0, |index| index + 1);
+    source[line_start..start].trim().is_empty()
+}
+
+fn snippet(text: &str) -> String {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut snippet = lines
+        .iter()
+        .take(SNIPPET_LINES)
+        .map(|line| line.trim_end())
+        .collect::<Vec<&str>>()
+        .join("\n");
+    if lines.len() > SNIPPET_LINES {
+        snippet.push_str("\n...");
+    }
+    snippet
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::language::Language;
+
+    fn hotspots(source: &str, language: Language, count: usize) -> Vec<Hotspot> {
+        let mut collector = Collector::default();
+        crate::analyze_with(source, language, |node, record| {
+            collector.visit(node, record, source)
+        })
+        .unwrap();
+        collector.finish(source, count)
+    }
+
+    #[test]
+    fn test_hotspots_point_at_deepest_lines() {
+        let source = "def f(a):\n    x = 1\n    if a:\n        return g(h(a))\n";
+        let found = hotspots(source, Language::Python, 2);
+        assert_eq!(found[0].kind, "return_statement");
+        assert_eq!(found[0].span.start.line, 4);
+        assert_eq!(found[0].span.start.column, 9);
+        assert_eq!(found[0].snippet, "return g(h(a))");
+        assert!(found[0].depth > found[1].depth);
+    }
+
+    #[test]
+    fn test_hotspots_do_not_overlap() {
+        let source = "if a:\n    if b:\n        c(d(e))\n";
+        let found = hotspots(source, Language::Python, 5);
+        for (i, a) in found.iter().enumerate() {
+            for b in &found[i + 1..] {
+                assert!(a.span.end.line < b.span.start.line || b.span.end.line < a.span.start.line);
+            }
+        }
+        assert_eq!(found[0].snippet, "c(d(e))");
+    }
+
+    #[test]
+    fn test_snippet_is_shortened() {
+        assert_eq!(snippet("a\nb  \nc\nd\n"), "a\nb\nc\n...");
+        assert_eq!(snippet("one line"), "one line");
+    }
+}
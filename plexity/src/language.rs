@@ -0,0 +1,300 @@
+/*  A module for mapping the supported programming languages onto
+their tree-sitter grammars.  */
+
+use crate::detect;
+use crate::error::{Error, UnknownLanguage};
+use serde::Serialize;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+use tree_sitter::Parser;
+
+/// A programming language (or filetype) with a bundled tree-sitter grammar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Language {
+    C,
+    Cpp,
+    Css,
+    Dockerfile,
+    Gitattributes,
+    Html,
+    Java,
+    JavaScript,
+    Json,
+    Markdown,
+    Php,
+    Python,
+    Rust,
+    Toml,
+    TypeScript,
+    Tsx,
+}
+
+impl Language {
+    /// Every supported language.
+    pub const ALL: [Language; 16] = [
+        Language::C,
+        Language::Cpp,
+        Language::Css,
+        Language::Dockerfile,
+        Language::Gitattributes,
+        Language::Html,
+        Language::Java,
+        Language::JavaScript,
+        Language::Json,
+        Language::Markdown,
+        Language::Php,
+        Language::Python,
+        Language::Rust,
+        Language::Toml,
+        Language::TypeScript,
+        Language::Tsx,
+    ];
+
+    /// The name used to select this language on the command line.
+    pub fn name(self) -> &'static str {
+        match self {
+            Language::C => "c",
+            Language::Cpp => "cpp",
+            Language::Css => "css",
+            Language::Dockerfile => "dockerfile",
+            Language::Gitattributes => "gitattributes",
+            Language::Html => "html",
+            Language::Java => "java",
+            Language::JavaScript => "javascript",
+            Language::Json => "json",
+            Language::Markdown => "markdown",
+            Language::Php => "php",
+            Language::Python => "python",
+            Language::Rust => "rust",
+            Language::Toml => "toml",
+            Language::TypeScript => "typescript",
+            Language::Tsx => "tsx",
+        }
+    }
+
+    /// The file extensions used for source files in this language.
+    pub fn extensions(self) -> &'static [&'static str] {
+        match self {
+            Language::C => &["c", "h"],
+            Language::Cpp => &["cpp", "cc", "cxx", "c++", "hpp", "hh", "hxx"],
+            Language::Css => &["css"],
+            Language::Dockerfile => &["dockerfile"],
+            Language::Gitattributes => &[],
+            Language::Html => &["html", "htm"],
+            Language::Java => &["java"],
+            Language::JavaScript => &["js", "mjs", "cjs", "jsx"],
+            Language::Json => &["json"],
+            Language::Markdown => &["md", "markdown"],
+            Language::Php => &["php"],
+            Language::Python => &["py", "pyi"],
+            Language::Rust => &["rs"],
+            Language::Toml => &["toml"],
+            Language::TypeScript => &["ts", "mts", "cts"],
+            Language::Tsx => &["tsx"],
+        }
+    }
+
+    /// Recognises the language of a file from its name or extension.
+    pub fn from_path(path: &Path) -> Option<Language> {
+        let file_name = path.file_name()?.to_str()?;
+        if file_name == "Dockerfile" || file_name.starts_with("Dockerfile.") {
+            return Some(Language::Dockerfile);
+        }
+        if file_name == ".gitattributes" {
+            return Some(Language::Gitattributes);
+        }
+
+        let extension = path.extension()?.to_str()?.to_lowercase();
+        Language::ALL
+            .iter()
+            .find(|language| language.extensions().contains(&extension.as_str()))
+            .copied()
+    }
+}
+
+impl fmt::Display for Language {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Language {
+    type Err = UnknownLanguage;
+
+    /// Looks up a language by its command-line name, or by one of the
+    /// other names editors and interpreters use for it.
+    fn from_str(name: &str) -> Result<Language, UnknownLanguage> {
+        detect::language_for_name(name).ok_or_else(|| UnknownLanguage::new(name))
+    }
+}
+
+/// Creates a parser that is loaded with the grammar for `language`.
+pub fn select_parser(language: Language) -> Result<Parser, Error> {
+    let grammar = match language {
+        Language::C => tree_sitter_c::language(),
+        Language::Cpp => tree_sitter_cpp::language(),
+        Language::Css => tree_sitter_css::language(),
+        Language::Dockerfile => tree_sitter_dockerfile::language(),
+        Language::Gitattributes => tree_sitter_gitattributes::language(),
+        Language::Html => tree_sitter_html::language(),
+        Language::Java => tree_sitter_java::language(),
+        Language::JavaScript => tree_sitter_javascript::language(),
+        Language::Json => tree_sitter_json::language(),
+        Language::Markdown => tree_sitter_md::language(),
+        Language::Php => tree_sitter_php::language_php(),
+        Language::Python => tree_sitter_python::language(),
+        Language::Rust => tree_sitter_rust::language(),
+        Language::Toml => tree_sitter_toml::language(),
+        Language::TypeScript => tree_sitter_typescript::language_typescript(),
+        Language::Tsx => tree_sitter_typescript::language_tsx(),
+    };
+
+    let mut parser: Parser = Parser::new();
+    parser.set_language(grammar)?;
+    Ok(parser)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_path() {
+        assert_eq!(
+            Language::from_path(Path::new("data/fibonacci/fib-rust.rs")),
+            Some(Language::Rust)
+        );
+        assert_eq!(
+            Language::from_path(Path::new("data/Dockerfile")),
+            Some(Language::Dockerfile)
+        );
+        assert_eq!(
+            Language::from_path(Path::new("repo/.gitattributes")),
+            Some(Language::Gitattributes)
+        );
+        assert_eq!(Language::from_path(Path::new("README")), None);
+        assert_eq!(Language::from_path(Path::new("notes.txt")), None);
+    }
+
+    #[test]
+    fn test_select_parser_c() {
+        let input_language: Language = "c".parse().unwrap();
+        let result_language = &Some(tree_sitter_c::language());
+        assert!(select_parser(input_language)
+            .unwrap()
+            .language()
+            .eq(result_language))
+    }
+
+    #[test]
+    fn test_select_parser_dockerfile() {
+        let input_language: Language = "dockerfile".parse().unwrap();
+        let result_language = &Some(tree_sitter_dockerfile::language());
+        assert!(select_parser(input_language)
+            .unwrap()
+            .language()
+            .eq(result_language))
+    }
+
+    #[test]
+    fn test_select_parser_java() {
+        let input_language: Language = "java".parse().unwrap();
+        let result_language = &Some(tree_sitter_java::language());
+        assert!(select_parser(input_language)
+            .unwrap()
+            .language()
+            .eq(result_language))
+    }
+
+    #[test]
+    fn test_select_parser_javascript() {
+        let input_language: Language = "javascript".parse().unwrap();
+        let result_language = &Some(tree_sitter_javascript::language());
+        assert!(select_parser(input_language)
+            .unwrap()
+            .language()
+            .eq(result_language))
+    }
+
+    #[test]
+    fn test_select_parser_json() {
+        let input_language: Language = "json".parse().unwrap();
+        let result_language = &Some(tree_sitter_json::language());
+        assert!(select_parser(input_language)
+            .unwrap()
+            .language()
+            .eq(result_language))
+    }
+
+    #[test]
+    fn test_select_parser_markdown() {
+        let input_language: Language = "markdown".parse().unwrap();
+        let result_language = &Some(tree_sitter_md::language());
+        assert!(select_parser(input_language)
+            .unwrap()
+            .language()
+            .eq(result_language))
+    }
+
+    #[test]
+    fn test_select_parser_python() {
+        let input_language: Language = "python".parse().unwrap();
+        let result_language = &Some(tree_sitter_python::language());
+        assert!(select_parser(input_language)
+            .unwrap()
+            .language()
+            .eq(result_language))
+    }
+
+    #[test]
+    fn test_select_parser_rust() {
+        let input_language: Language = "rust".parse().unwrap();
+        let result_language = &Some(tree_sitter_rust::language());
+        assert!(select_parser(input_language)
+            .unwrap()
+            .language()
+            .eq(result_language))
+    }
+
+    #[test]
+    fn test_select_parser_toml() {
+        let input_language: Language = "toml".parse().unwrap();
+        let result_language = &Some(tree_sitter_toml::language());
+        assert!(select_parser(input_language)
+            .unwrap()
+            .language()
+            .eq(result_language))
+    }
+
+    #[test]
+    fn test_from_str_accepts_aliases() {
+        assert_eq!("Python".parse::<Language>().unwrap(), Language::Python);
+        assert_eq!("c++".parse::<Language>().unwrap(), Language::Cpp);
+        assert_eq!("ts".parse::<Language>().unwrap(), Language::TypeScript);
+    }
+
+    #[test]
+    fn test_from_str_rejects_unknown_names() {
+        let err = "go".parse::<Language>().unwrap_err();
+        assert_eq!(err.name, "go");
+        assert!(err.suggestions.is_empty());
+        assert!(err
+            .to_string()
+            .contains("supported languages are c, cpp, css"));
+    }
+
+    #[test]
+    fn test_unknown_language_suggestions() {
+        let err = "pyhton".parse::<Language>().unwrap_err();
+        assert_eq!(err.suggestions, vec![Language::Python]);
+        assert!(err.to_string().contains("did you mean python?"));
+
+        let err = "java-script".parse::<Language>().unwrap_err();
+        assert_eq!(err.suggestions, vec![Language::JavaScript]);
+
+        let err = "type".parse::<Language>().unwrap_err();
+        assert_eq!(err.suggestions, vec![Language::TypeScript]);
+    }
+}
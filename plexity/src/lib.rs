@@ -0,0 +1,59 @@
+/*  A library for creating an abstract syntax tree from input source code
+and evaluating the resultant tree's complexity.  */
+
+pub mod baseline;
+pub mod check;
+pub mod cognitive;
+pub mod config;
+pub mod cyclomatic;
+pub mod detect;
+pub mod diff;
+pub mod error;
+pub mod functions;
+pub mod git;
+pub mod halstead;
+pub mod heatmap;
+pub mod history;
+pub mod hotspots;
+pub mod language;
+pub mod maintainability;
+pub mod output;
+pub mod project;
+pub mod report;
+pub mod suppress;
+pub mod syntax;
+pub mod trace;
+pub mod traverse;
+pub mod walk;
+
+pub use config::Config;
+pub use error::{ConfigError, Error, UnknownLanguage};
+pub use halstead::Halstead;
+pub use hotspots::Hotspot;
+pub use language::{select_parser, Language};
+pub use maintainability::Maintainability;
+pub use project::{FileReport, Summary};
+pub use report::{FunctionReport, Metric, Metrics, NodeRecord, Position, Report, Span};
+pub use traverse::{traverse_tree, traverse_tree_with};
+
+/// Parses `source` with the grammar for `language` into a syntax tree.
+pub fn parse(source: &str, language: Language) -> Result<tree_sitter::Tree, Error> {
+    select_parser(language)?
+        .parse(source, None)
+        .ok_or(Error::Parse)
+}
+
+/// Parses `source` with the grammar for `language` and returns its scorecard.
+pub fn analyze(source: &str, language: Language) -> Result<Report, Error> {
+    let parser = select_parser(language)?;
+    traverse_tree(source, parser, language)
+}
+
+/// Like `analyze`, but calls `on_node` for every node in the syntax tree.
+pub fn analyze_with<F>(source: &str, language: Language, on_node: F) -> Result<Report, Error>
+where
+    F: FnMut(tree_sitter::Node, &NodeRecord),
+{
+    let parser = select_parser(language)?;
+    traverse_tree_with(source, parser, language, on_node)
+}
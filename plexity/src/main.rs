@@ -1,196 +1,753 @@
 /*  A module for creating an abstract syntax tree from input source code
 and evaluating the resultant tree's complexity.  */
 
-use std::env;
-use std::fs;
-use std::process;
-use tree_sitter::{Node, Parser, Range, Tree};
+mod cli;
 
-struct Config {
-    filepath: String,
-    language: String,
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, IsTerminal};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use clap::{CommandFactory, Parser};
+use cli::{AnalyzeArgs, BaselineArgs, CheckArgs, Cli, Colour, Command, CompareArgs, DiffArgs};
+use cli::{HistoryArgs, Rows, SeriesFormat, TraceArgs, TreeArgs, TreeFormat, Verbosity, WalkArgs};
+use plexity::baseline::Baseline;
+use plexity::check;
+use plexity::diff::{self, FileDelta};
+use plexity::git::{self, Status};
+use plexity::history::{self, Sample};
+use plexity::output::{self, Format};
+use plexity::walk::{PathGlobs, Selection};
+use plexity::{heatmap, hotspots, project, syntax, trace, walk};
+use plexity::{Config, ConfigError, FileReport, Language, Metric, NodeRecord};
+
+// What to print or gather while analysing each file, beyond its metrics
+struct Options {
+    header: bool,
+    trace: Option<Trace>,
+    nodes: bool,
+    // How many hotspots to keep, when they are wanted
+    hotspots: Option<usize>,
+    // Whether to keep the source shaded by depth, for the HTML report
+    heatmap: bool,
+    // The optional metrics to keep, when not all of them are wanted
+    metrics: Option<Vec<Metric>>,
 }
 
-impl Config {
-    fn build(args: &[String]) -> Result<Config, &'static str> {
-        if args.len() < 3 {
-            return Err("Both an input filepath and a programming language must be entered as CLI arguments.");
-        }
+impl Options {
+    // Reports that are only used for their metrics
+    const SILENT: Options = Options {
+        header: false,
+        trace: None,
+        nodes: false,
+        hotspots: None,
+        heatmap: false,
+        metrics: None,
+    };
+}
 
-        let filepath = args[1].clone();
-        let language = args[2].clone();
+// The nodes to trace, and how to print them
+struct Trace {
+    filter: trace::Filter,
+    format: Format,
+}
 
-        Ok(Config { filepath, language })
+impl Trace {
+    fn from_args(args: TraceArgs) -> Option<Trace> {
+        args.trace.then_some(Trace {
+            filter: trace::Filter {
+                minimum_depth: args.trace_min_depth,
+                kinds: args.trace_kind,
+                lines: args.trace_lines,
+            },
+            format: args.trace_format,
+        })
     }
 }
 
-fn read_file(filepath: String) -> String {
-    println!();
-    println!("Selected input file: {}", filepath);
+fn read_file(filepath: &Path) -> io::Result<String> {
+    fs::read_to_string(filepath)
+}
+
+fn analyze_file(
+    path: &Path,
+    selection: &Selection,
+    options: &Options,
+) -> Result<FileReport, String> {
+    let file_contents: String = read_file(path).map_err(|err| err.to_string())?;
+    let language: Language = selection
+        .language
+        .or_else(|| selection.mappings.detect(path, &file_contents))
+        .ok_or("unable to detect the programming language (try passing --language)")?;
+    let mut nodes: Vec<NodeRecord> = Vec::new();
+
+    if options.header {
+        println!();
+        println!("Selected input file: {}", path.display());
+        println!("Selected programming language grammar: {}", language);
+        println!();
+    }
 
-    let file_contents: String =
-        fs::read_to_string(filepath).expect("Should have been able to read the file");
+    let display_path = path.display().to_string();
+    let mut maximum_depth = 0;
+    let mut collector = hotspots::Collector::default();
+    let mut heatmap_collector = heatmap::Collector::default();
+    let mut report = plexity::analyze_with(&file_contents, language, |node, record| {
+        maximum_depth = maximum_depth.max(record.depth);
+        if let Some(trace) = options.trace.as_ref().filter(|t| t.filter.matches(record)) {
+            match trace.format {
+                Format::Json => println!("{}", trace::json_line(&display_path, record)),
+                _ => println!("{}", trace::text_line(record, maximum_depth)),
+            }
+        }
+        if options.nodes {
+            nodes.push(record.clone());
+        }
+        if options.hotspots.is_some() {
+            collector.visit(node, record, &file_contents);
+        }
+        if options.heatmap {
+            heatmap_collector.visit(node, record, &file_contents);
+        }
+    })
+    .map_err(|err| err.to_string())?;
+    if let Some(metrics) = &options.metrics {
+        report.retain_metrics(metrics);
+    }
 
-    return file_contents;
+    Ok(FileReport {
+        path: display_path,
+        language,
+        report,
+        nodes: if options.nodes { Some(nodes) } else { None },
+        hotspots: options
+            .hotspots
+            .map(|count| collector.finish(&file_contents, count)),
+        heatmap: options
+            .heatmap
+            .then(|| heatmap_collector.finish(&file_contents)),
+    })
 }
 
-fn select_parser(language: String) -> Parser {
-    let mut parser: Parser = Parser::new();
-
-    println!("Selected programming language grammar: {}", language);
-    println!();
-
-    match language.as_str() {
-        "c" => parser.set_language(tree_sitter_c::language()).unwrap(),
-        "cpp" => parser.set_language(tree_sitter_cpp::language()).unwrap(),
-        "css" => parser.set_language(tree_sitter_css::language()).unwrap(),
-        "dockerfile" => parser
-            .set_language(tree_sitter_dockerfile::language())
-            .unwrap(),
-        "gitattributes" => parser
-            .set_language(tree_sitter_gitattributes::language())
-            .unwrap(),
-        "html" => parser.set_language(tree_sitter_html::language()).unwrap(),
-        "java" => parser.set_language(tree_sitter_java::language()).unwrap(),
-        "javascript" => parser
-            .set_language(tree_sitter_javascript::language())
-            .unwrap(),
-        "json" => parser.set_language(tree_sitter_json::language()).unwrap(),
-        "markdown" => parser.set_language(tree_sitter_md::language()).unwrap(),
-        "php" => parser
-            .set_language(tree_sitter_php::language_php())
-            .unwrap(),
-        "python" => parser.set_language(tree_sitter_python::language()).unwrap(),
-        "rust" => parser.set_language(tree_sitter_rust::language()).unwrap(),
-        "toml" => parser.set_language(tree_sitter_toml::language()).unwrap(),
-        "typescript" => parser
-            .set_language(tree_sitter_typescript::language_typescript())
-            .unwrap(),
-        // Need to do something about this "wildcard" match statement (required by compiler)
-        &_ => parser.set_language(tree_sitter_python::language()).unwrap(),
-    }
-    return parser;
+// A programming language may still be given after the paths, as in
+// `plexity hello-world.py python`, as long as it does not look like a path
+fn split_language(
+    mut paths: Vec<String>,
+    mut language: Option<Language>,
+) -> Result<(Vec<PathBuf>, Option<Language>), String> {
+    if language.is_none() && paths.len() > 1 {
+        if let Some(last) = paths.last() {
+            let is_path = last.contains(['.', '/', '\\']) || Path::new(last).exists();
+            if !is_path {
+                language = Some(last.parse().map_err(|err| format!("{err}"))?);
+                paths.pop();
+            }
+        }
+    }
+    Ok((paths.into_iter().map(PathBuf::from).collect(), language))
 }
 
-fn traverse_tree(source_code: String, mut parser: Parser, language: String) {
-    let parse_tree: Tree = parser.parse(source_code, None).unwrap();
-    let root_node: Node = parse_tree.root_node();
-
-    // Unpack nodes recursively, starting with the root node
-    let mut node_count: i32 = 0;
-    let mut plexity_score: i32 = 0;
-    let current_depth: i32 = 0;
-    let starting_maximum_depth: i32 = 0;
-    let concluding_maximum_depth: i32;
-    let mut cyclomatic_count: i32 = 0;
-    (
-        node_count,
-        concluding_maximum_depth,
-        plexity_score,
-        cyclomatic_count,
-    ) = unpack_node(
-        root_node,
-        node_count,
-        current_depth,
-        starting_maximum_depth,
-        plexity_score,
-        cyclomatic_count,
-    );
-    println!("\n\n============ PLEXITY SCORECARD ============\n");
-    println!("  - Number of nodes found in tree: {}", node_count);
-    println!(
-        "  - Maximum depth of syntax tree: {}",
-        concluding_maximum_depth
-    );
-    println!("  - Combined depths of all nodes: {}", plexity_score);
-    let plexity_score_float = plexity_score as f32;
-    let node_count_float = node_count as f32;
-    println!(
-        "  - Average depth across syntax tree: {:.2}",
-        plexity_score_float / node_count_float
-    );
-    if language == "python" {
-        println!("  - Cyclomatic complexity: {}", cyclomatic_count + 1);
+// The files to analyse in directories, with the globs given on the command
+// line left out as well as those in the configuration file
+fn selection(
+    config: &Config,
+    language: Option<Language>,
+    args: WalkArgs,
+) -> Result<Selection, String> {
+    let mut selection = config.selection(language);
+    if !args.exclude.is_empty() {
+        let globs = PathGlobs::new(Path::new("."), &args.exclude).map_err(|err| err.to_string())?;
+        selection.exclude.push(globs);
+    }
+    selection.include_ignored = args.no_ignore;
+    Ok(selection)
+}
+
+// Expands the given paths, printing any problem along the way
+fn collect_files(paths: &[PathBuf], selection: &Selection) -> Option<Vec<PathBuf>> {
+    match walk::collect_files(paths, selection) {
+        Ok(files) if files.is_empty() => {
+            println!("No source files were found in the given paths.");
+            None
+        }
+        Ok(files) => Some(files),
+        Err(err) => {
+            println!("Problem finding input files: {err}");
+            None
+        }
     }
 }
 
-fn unpack_node(
-    node: Node,
-    mut node_count: i32,
-    current_depth: i32,
-    mut maximum_depth: i32,
-    mut plexity_score: i32,
-    mut cyclomatic_count: i32,
-) -> (i32, i32, i32, i32) {
-    for i in 0..node.child_count() {
-        node_count += 1;
-        let child = node.child(i).unwrap();
-        let child_range: Range = child.range();
-
-        if current_depth > maximum_depth {
-            maximum_depth = current_depth;
+// Analyses every file, printing a problem for each one that fails
+fn analyze_files(
+    files: &[PathBuf],
+    selection: &Selection,
+    options: &Options,
+    verbosity: &Verbosity,
+    mut on_report: impl FnMut(&FileReport),
+) -> (Vec<FileReport>, bool) {
+    let mut reports: Vec<FileReport> = Vec::new();
+    let mut failed = false;
+    for path in files {
+        match analyze_file(path, selection, options) {
+            Ok(file_report) => {
+                if verbosity.verbose {
+                    eprintln!("Analysed {} as {}", file_report.path, file_report.language);
+                }
+                on_report(&file_report);
+                reports.push(file_report);
+            }
+            Err(err) => {
+                eprintln!("Problem analysing {}: {err}", path.display());
+                failed = true;
+            }
+        }
+    }
+    (reports, failed)
+}
+
+fn run_analyze(args: AnalyzeArgs, config: &Config, verbosity: &Verbosity) -> ExitCode {
+    let hotspot_count = args.hotspot_count();
+    let (paths, language) = match split_language(args.paths, args.language.language) {
+        Ok(split) => split,
+        Err(err) => {
+            println!("Problem parsing arguments: {err}");
+            return ExitCode::FAILURE;
+        }
+    };
+    if paths.is_empty() {
+        println!(
+            "Problem parsing arguments: At least one input path must be entered as a CLI argument."
+        );
+        return ExitCode::FAILURE;
+    }
+    let format = output_format(args.format.format, config);
+    if args.trace.trace && format != Format::Text {
+        println!("Problem parsing arguments: --trace prints alongside the text scorecard (try --trace-format json).");
+        return ExitCode::FAILURE;
+    }
+    let selection = match selection(config, language, args.walk) {
+        Ok(selection) => selection,
+        Err(err) => {
+            println!("Problem parsing arguments: {err}");
+            return ExitCode::FAILURE;
+        }
+    };
+    let Some(files) = collect_files(&paths, &selection) else {
+        return ExitCode::FAILURE;
+    };
+
+    // A single file keeps its own document; anything more is rolled up
+    let single_file = paths.len() == 1 && files == paths;
+    let trace = Trace::from_args(args.trace);
+    // A JSON Lines trace is the whole output, so that it can be piped on
+    let json_trace = trace
+        .as_ref()
+        .is_some_and(|trace| trace.format == Format::Json);
+    let text = format == Format::Text && !json_trace;
+    let options = Options {
+        // Headers tell apart the scorecards (or traces) of each file
+        header: text && !verbosity.quiet && (!single_file || trace.is_some()),
+        trace,
+        nodes: (format == Format::Json && args.nodes)
+            || (format.separator().is_some() && args.rows == Rows::Nodes),
+        hotspots: hotspot_count,
+        heatmap: args.html.is_some(),
+        metrics: args.metrics.or_else(|| config.metrics.clone()),
+    };
+    let (reports, mut failed) =
+        analyze_files(&files, &selection, &options, verbosity, |file_report| {
+            if text && (single_file || !verbosity.quiet) {
+                if options.trace.is_some() {
+                    println!();
+                }
+                print!("{}", output::text::scorecard(&file_report.report));
+            }
+        });
+
+    if text && !single_file {
+        print!(
+            "\n\n{}",
+            output::text::summary(&project::summarize(&reports))
+        );
+    }
+    if let Some(count) = hotspot_count.filter(|_| text) {
+        print!("\n\n{}", output::text::hotspots(&reports, count));
+    }
+    if format == Format::Json {
+        if single_file && reports.len() == 1 {
+            println!("{}", output::json::document(&reports[0]));
+        } else {
+            let summary = project::summarize(&reports);
+            println!("{}", output::json::project_document(&reports, &summary));
+        }
+    }
+    if let Some(separator) = format.separator() {
+        let table = match args.rows {
+            Rows::Files => output::csv::files(&reports, separator),
+            Rows::Functions => output::csv::functions(&reports, separator),
+            Rows::Nodes => output::csv::nodes(&reports, separator),
         };
+        print!("{table}");
+    }
+    if let Some(path) = &args.html {
+        let page = output::html::report(&reports, &project::summarize(&reports));
+        if let Err(err) = fs::write(path, page) {
+            eprintln!("Problem writing {}: {err}", path.display());
+            failed = true;
+        }
+    }
+
+    if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+fn run_tree(args: TreeArgs, config: &Config) -> ExitCode {
+    let cutoff = syntax::Cutoff {
+        named_only: args.named_only,
+        maximum_depth: args.max_depth,
+        lines: args.lines,
+    };
+    if args.format == TreeFormat::Sexp && cutoff != syntax::Cutoff::default() {
+        println!("Problem parsing arguments: --named-only, --max-depth and --lines cannot be used with --format sexp.");
+        return ExitCode::FAILURE;
+    }
+    let drawing = read_file(&args.path)
+        .map_err(|err| err.to_string())
+        .and_then(|source| {
+            let language = args
+                .language
+                .language
+                .or_else(|| config.mappings.detect(&args.path, &source))
+                .ok_or("unable to detect the programming language (try passing --language)")?;
+            if args.format == TreeFormat::Sexp {
+                let tree = plexity::parse(&source, language).map_err(|err| err.to_string())?;
+                return Ok(format!("{}\n", tree.root_node().to_sexp()));
+            }
+            let mut collector = syntax::Collector::new(cutoff);
+            plexity::analyze_with(&source, language, |node, record| {
+                collector.visit(node, record, &source)
+            })
+            .map_err(|err| err.to_string())?;
+            let nodes = collector.finish();
+            Ok(match args.format {
+                TreeFormat::Dot => output::graph::dot(&nodes),
+                TreeFormat::Mermaid => output::graph::mermaid(&nodes),
+                _ => {
+                    let colour = match args.colour {
+                        Colour::Always => true,
+                        Colour::Never => false,
+                        Colour::Auto => {
+                            io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none()
+                        }
+                    };
+                    let root = args.path.display().to_string();
+                    output::text::tree(&root, &nodes, colour)
+                }
+            })
+        });
+    match drawing {
+        Ok(drawing) => {
+            print!("{drawing}");
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            eprintln!("Problem analysing {}: {err}", args.path.display());
+            ExitCode::FAILURE
+        }
+    }
+}
 
-        let is_cyclomatic = cyclomatic_check(child.to_sexp());
+fn run_compare(args: CompareArgs, config: &Config) -> ExitCode {
+    let selection = config.selection(args.language.language);
+    let options = Options {
+        metrics: config.metrics.clone(),
+        ..Options::SILENT
+    };
+    let before = analyze_file(&args.before, &selection, &options);
+    let after = analyze_file(&args.after, &selection, &options);
+    let (before, after) = match (before, after) {
+        (Ok(before), Ok(after)) => (before, after),
+        (before, after) => {
+            for (path, result) in [(&args.before, before), (&args.after, after)] {
+                if let Err(err) = result {
+                    eprintln!("Problem analysing {}: {err}", path.display());
+                }
+            }
+            return ExitCode::FAILURE;
+        }
+    };
+
+    match output_format(args.format.format, config) {
+        Format::Text => print!("{}", output::text::comparison(&before, &after)),
+        Format::Json => println!("{}", output::json::comparison_document(&before, &after)),
+        format => {
+            let separator = format.separator().unwrap();
+            print!("{}", output::csv::files(&[before, after], separator));
+        }
+    }
+    ExitCode::SUCCESS
+}
 
-        if is_cyclomatic {
-            cyclomatic_count += 1;
+// Analyses a changed file in both revisions, skipping files that are not
+// source code
+fn diff_file(
+    change: &git::Change,
+    args: &DiffArgs,
+    config: &Config,
+) -> Result<Option<FileDelta>, String> {
+    let repository = Path::new(".");
+    let after_source =
+        git::show(repository, &args.head, &change.path).map_err(|err| err.to_string())?;
+    let path = Path::new(&change.path);
+    let Some(language) = args
+        .language
+        .language
+        .or_else(|| config.mappings.detect(path, &after_source))
+    else {
+        return Ok(None);
+    };
+    let after = plexity::analyze(&after_source, language).map_err(|err| err.to_string())?;
+
+    let before = match change.status {
+        Status::Added => None,
+        _ => {
+            let old_path = change.old_path.as_deref().unwrap_or(&change.path);
+            let source =
+                git::show(repository, &args.base, old_path).map_err(|err| err.to_string())?;
+            let report = plexity::analyze(&source, language).map_err(|err| err.to_string())?;
+            Some((source, report))
         }
+    };
 
-        // Print node ranges (a sanity check to ultimately be removed)
+    let functions = diff::function_deltas(
+        before
+            .as_ref()
+            .map(|(source, report)| (source.as_str(), report)),
+        (&after_source, &after),
+    );
+    Ok(Some(FileDelta {
+        path: change.path.clone(),
+        old_path: change.old_path.clone(),
+        status: change.status,
+        language,
+        before: before.map(|(_, report)| report.metrics),
+        after: after.metrics,
+        functions,
+    }))
+}
+
+fn run_diff(args: DiffArgs, config: &Config, verbosity: &Verbosity) -> ExitCode {
+    let format = output_format(args.format.format, config);
+    if format.separator().is_some() {
         println!(
-            "#{} | depth:{}/{} | beg:{} end:{} | s-exp: {} | cyclo?: {}",
-            node_count,
-            current_depth,
-            maximum_depth,
-            child_range.start_point,
-            child_range.end_point,
-            child.to_sexp(),
-            is_cyclomatic
+            "Problem parsing arguments: diff cannot be written as {} (try --format json).",
+            format.name()
         );
+        return ExitCode::FAILURE;
+    }
+    let changes = match git::changed_files(Path::new("."), &args.base, &args.head) {
+        Ok(changes) => changes,
+        Err(err) => {
+            eprintln!("Problem reading the git repository: {err}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut files = Vec::new();
+    let mut failed = false;
+    // Deleted files have no functions left to review
+    for change in changes
+        .iter()
+        .filter(|change| change.status != Status::Deleted)
+    {
+        match diff_file(change, &args, config) {
+            Ok(Some(file)) => {
+                if verbosity.verbose {
+                    eprintln!("Analysed {} as {}", file.path, file.language);
+                }
+                files.push(file);
+            }
+            Ok(None) => {}
+            Err(err) => {
+                eprintln!("Problem analysing {}: {err}", change.path);
+                failed = true;
+            }
+        }
+    }
 
-        plexity_score += current_depth;
+    match format {
+        Format::Json => println!(
+            "{}",
+            output::json::diff_document(&args.base, &args.head, &files)
+        ),
+        _ => print!("{}", output::text::diff(&files)),
+    }
+    if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
 
-        (node_count, maximum_depth, plexity_score, cyclomatic_count) = unpack_node(
-            child,
-            node_count,
-            current_depth + 1,
-            maximum_depth,
-            plexity_score,
-            cyclomatic_count,
-        );
+// Analyses the source files at or below the followed path as they were at
+// `commit`, skipping files that are not source code
+fn history_sample(
+    commit: &git::Commit,
+    args: &HistoryArgs,
+    config: &Config,
+) -> Result<Sample, String> {
+    let repository = Path::new(".");
+    let paths = git::files(repository, &commit.hash, &args.path).map_err(|err| err.to_string())?;
+    let mut files = Vec::new();
+    for path in paths {
+        let source = match git::show(repository, &commit.hash, &path) {
+            Ok(source) => source,
+            // Files that are not UTF-8 text cannot be source code
+            Err(err) if err.kind() == io::ErrorKind::InvalidData => continue,
+            Err(err) => return Err(format!("{path}: {err}")),
+        };
+        let Some(language) = args
+            .language
+            .language
+            .or_else(|| config.mappings.detect(Path::new(&path), &source))
+        else {
+            continue;
+        };
+        let report = plexity::analyze(&source, language).map_err(|err| format!("{path}: {err}"))?;
+        files.push(FileReport::new(path, language, report));
     }
+    Ok(Sample::new(commit, &files))
+}
 
-    return (node_count, maximum_depth, plexity_score, cyclomatic_count);
+fn run_history(args: HistoryArgs, config: &Config, verbosity: &Verbosity) -> ExitCode {
+    let commits = match git::log(Path::new("."), &args.path) {
+        Ok(commits) => commits,
+        Err(err) => {
+            eprintln!("Problem reading the git repository: {err}");
+            return ExitCode::FAILURE;
+        }
+    };
+    if commits.is_empty() {
+        println!("No commits were found for {}.", args.path.display());
+        return ExitCode::FAILURE;
+    }
+
+    let mut samples = Vec::new();
+    let mut failed = false;
+    for commit in history::sample_commits(commits, args.every, args.limit) {
+        match history_sample(&commit, &args, config) {
+            Ok(sample) => {
+                if verbosity.verbose {
+                    eprintln!(
+                        "Analysed {} file(s) at {}",
+                        sample.file_count, sample.commit
+                    );
+                }
+                samples.push(sample);
+            }
+            Err(err) => {
+                eprintln!("Problem analysing {}: {err}", commit.hash);
+                failed = true;
+            }
+        }
+    }
+
+    match args.format {
+        SeriesFormat::Csv => print!("{}", output::csv::history(&samples, ',')),
+        SeriesFormat::Tsv => print!("{}", output::csv::history(&samples, '\t')),
+        SeriesFormat::Json => println!(
+            "{}",
+            output::json::history_document(&args.path.display().to_string(), &samples)
+        ),
+    }
+    if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
 }
 
-fn cyclomatic_check(s_expression: String) -> bool {
-    return s_expression.starts_with("(if_statement")
-        || s_expression.starts_with("(elif_clause")
-        || s_expression.starts_with("(for_statement")
-        || s_expression.starts_with("(while_statement")
-        || s_expression.starts_with("(except_clause")
-        || s_expression.starts_with("(with_statement")
-        || s_expression.starts_with("(assert_statement")
-        || s_expression.starts_with("(list_comprehension")
-        || s_expression.starts_with("(set_comprehension")
-        || s_expression.starts_with("(dictionary_comprehension")
-        || s_expression.starts_with("(boolean_operator");
+fn run_check(args: CheckArgs, config: &Config, verbosity: &Verbosity) -> ExitCode {
+    // Limits given on the command line take priority over configured ones
+    let limits = args.thresholds.limits();
+    if limits.is_empty() && !config.has_limits() {
+        println!("Problem parsing arguments: No limits were given (try passing --max-depth).");
+        return ExitCode::FAILURE;
+    }
+    let baseline = match args.baseline.as_deref().map(Baseline::load).transpose() {
+        Ok(baseline) => baseline,
+        Err(err) => {
+            println!("Problem reading baseline: {err}");
+            return ExitCode::FAILURE;
+        }
+    };
+    let selection = match selection(config, args.language.language, args.walk) {
+        Ok(selection) => selection,
+        Err(err) => {
+            println!("Problem parsing arguments: {err}");
+            return ExitCode::FAILURE;
+        }
+    };
+    let Some(files) = collect_files(&args.paths, &selection) else {
+        return ExitCode::FAILURE;
+    };
+
+    let (reports, mut failed) =
+        analyze_files(&files, &selection, &Options::SILENT, verbosity, |_| {});
+    let (violations, known): (Vec<check::Violation>, Vec<check::Violation>) = reports
+        .iter()
+        .flat_map(|file_report| {
+            let limits = limits.or(&config.limits(file_report.language));
+            check::check(file_report, &limits)
+        })
+        .partition(|violation| {
+            baseline
+                .as_ref()
+                .is_none_or(|baseline| baseline.is_regression(violation, config.root()))
+        });
+
+    match output_format(args.format.format, config) {
+        Format::Text => {
+            if !violations.is_empty() {
+                print!("{}", output::text::violations(&violations));
+            }
+            if !verbosity.quiet {
+                print!(
+                    "{} limit(s) exceeded across {} checked file(s)",
+                    violations.len(),
+                    reports.len()
+                );
+                if baseline.is_some() {
+                    print!(", besides {} recorded in the baseline", known.len());
+                }
+                println!(".");
+            }
+        }
+        Format::Json => println!(
+            "{}",
+            output::json::check_document(reports.len(), &violations)
+        ),
+        format => {
+            let separator = format.separator().unwrap();
+            print!("{}", output::csv::violations(&violations, separator));
+        }
+    }
+    if let Some(path) = &args.sarif {
+        // SARIF counts columns in UTF-16 code units, which needs the source
+        let mut sources = HashMap::new();
+        for violation in &violations {
+            if !sources.contains_key(&violation.path) {
+                if let Ok(source) = fs::read_to_string(&violation.path) {
+                    sources.insert(violation.path.clone(), source);
+                }
+            }
+        }
+        if let Err(err) = fs::write(path, output::sarif::log(&violations, &sources) + "\n") {
+            eprintln!("Problem writing {}: {err}", path.display());
+            failed = true;
+        }
+    }
+
+    if failed || !violations.is_empty() {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
 }
 
-fn main() {
-    let args: Vec<String> = env::args().collect();
+fn run_baseline(args: BaselineArgs, config: &Config, verbosity: &Verbosity) -> ExitCode {
+    let selection = match selection(config, args.language.language, args.walk) {
+        Ok(selection) => selection,
+        Err(err) => {
+            println!("Problem parsing arguments: {err}");
+            return ExitCode::FAILURE;
+        }
+    };
+    let Some(files) = collect_files(&args.paths, &selection) else {
+        return ExitCode::FAILURE;
+    };
+
+    let (reports, failed) = analyze_files(&files, &selection, &Options::SILENT, verbosity, |_| {});
+    let baseline = Baseline::new(&reports, config.root());
+    if let Err(err) = baseline.save(&args.output) {
+        eprintln!("Problem writing {}: {err}", args.output.display());
+        return ExitCode::FAILURE;
+    }
+    if !verbosity.quiet {
+        println!(
+            "Recorded {} file(s) and {} function(s) in {}.",
+            baseline.files.len(),
+            baseline.function_count(),
+            args.output.display()
+        );
+    }
+
+    if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
 
-    let config: Config = Config::build(&args).unwrap_or_else(|err| {
-        println!("Problem parsing arguments: {err}");
-        process::exit(1);
-    });
+// An output format given on the command line takes priority over one
+// given in the configuration file
+fn output_format(format: Option<Format>, config: &Config) -> Format {
+    format.or(config.format).unwrap_or(Format::Text)
+}
 
-    let file_contents: String = read_file(config.filepath);
-    let language: String = config.language;
-    let parser: Parser = select_parser(language.clone());
+// Reads the given configuration file, or else the nearest one above the
+// working directory
+fn load_config(path: Option<&Path>) -> Result<Config, ConfigError> {
+    match path {
+        Some(path) => Config::load(path),
+        None => {
+            let directory = std::env::current_dir().map_err(|err| ConfigError::new(".", err))?;
+            Config::discover(&directory)
+        }
+    }
+}
 
-    traverse_tree(file_contents, parser, language);
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let verbosity = cli.verbosity;
+    let command = cli.command.unwrap_or(Command::Analyze(cli.analyze));
+
+    // Listing languages and completing commands never depend on the settings
+    let config = match command {
+        Command::Languages | Command::Completions { .. } => Config::default(),
+        _ => match load_config(cli.config.as_deref()) {
+            Ok(config) => config,
+            Err(err) => {
+                eprintln!("Problem reading configuration: {err}");
+                return ExitCode::FAILURE;
+            }
+        },
+    };
+
+    match command {
+        Command::Analyze(args) => run_analyze(args, &config, &verbosity),
+        Command::Tree(args) => run_tree(args, &config),
+        Command::Compare(args) => run_compare(args, &config),
+        Command::Diff(args) => run_diff(args, &config, &verbosity),
+        Command::History(args) => run_history(args, &config, &verbosity),
+        Command::Languages => {
+            print!("{}", output::text::languages());
+            ExitCode::SUCCESS
+        }
+        Command::Check(args) => run_check(args, &config, &verbosity),
+        Command::Baseline(args) => run_baseline(args, &config, &verbosity),
+        Command::Completions { shell } => {
+            let mut command = Cli::command();
+            let name = command.get_name().to_string();
+            clap_complete::generate(shell, &mut command, name, &mut io::stdout());
+            ExitCode::SUCCESS
+        }
+    }
 }
 
 #[cfg(test)]
@@ -199,7 +756,7 @@ mod tests {
 
     #[test]
     fn test_read_file() {
-        let full_result = read_file("src/main.rs".to_string());
+        let full_result = read_file(Path::new("src/main.rs")).unwrap();
         let partial_result = &full_result[0..49];
         assert_eq!(
             partial_result,
@@ -208,65 +765,107 @@ mod tests {
     }
 
     #[test]
-    fn test_select_parser_c() {
-        let input_language = "c".to_string();
-        let result_language = &Some(tree_sitter_c::language());
-        assert!(select_parser(input_language).language().eq(result_language))
-    }
-
-    #[test]
-    fn test_select_parser_dockerfile() {
-        let input_language = "dockerfile".to_string();
-        let result_language = &Some(tree_sitter_dockerfile::language());
-        assert!(select_parser(input_language).language().eq(result_language))
-    }
-
-    #[test]
-    fn test_select_parser_java() {
-        let input_language = "java".to_string();
-        let result_language = &Some(tree_sitter_java::language());
-        assert!(select_parser(input_language).language().eq(result_language))
+    fn test_cli_definition() {
+        Cli::command().debug_assert();
     }
 
     #[test]
-    fn test_select_parser_javascript() {
-        let input_language = "javascript".to_string();
-        let result_language = &Some(tree_sitter_javascript::language());
-        assert!(select_parser(input_language).language().eq(result_language))
+    fn test_legacy_language_argument() {
+        let paths = vec!["hello.py".to_string(), "python".to_string()];
+        let (paths, language) = split_language(paths, None).unwrap();
+        assert_eq!(paths, vec![PathBuf::from("hello.py")]);
+        assert_eq!(language, Some(Language::Python));
+
+        let paths = vec!["hello.go".to_string(), "go".to_string()];
+        assert!(split_language(paths, None).is_err());
     }
 
     #[test]
-    fn test_select_parser_json() {
-        let input_language = "json".to_string();
-        let result_language = &Some(tree_sitter_json::language());
-        assert!(select_parser(input_language).language().eq(result_language))
-    }
+    fn test_subcommands_parse() {
+        let cli = Cli::try_parse_from(["plexity", "check", "src", "--max-depth", "8"]).unwrap();
+        match cli.command {
+            Some(Command::Check(args)) => assert_eq!(args.thresholds.max_depth, Some(8)),
+            _ => panic!("expected the check subcommand"),
+        }
+        let cli = Cli::try_parse_from(["plexity", "a.py", "--format", "json"]).unwrap();
+        assert!(cli.command.is_none());
+        assert_eq!(cli.analyze.format.format, Some(Format::Json));
+        assert!(Cli::try_parse_from(["plexity", "a.py", "--language", "go"]).is_err());
+
+        let cli = Cli::try_parse_from(["plexity", "history", "src", "--every", "5"]).unwrap();
+        match cli.command {
+            Some(Command::History(args)) => {
+                assert_eq!(args.every, 5);
+                assert_eq!(args.limit, None);
+                assert_eq!(args.format, SeriesFormat::Csv);
+            }
+            _ => panic!("expected the history subcommand"),
+        }
 
-    #[test]
-    fn test_select_parser_markdown() {
-        let input_language = "markdown".to_string();
-        let result_language = &Some(tree_sitter_md::language());
-        assert!(select_parser(input_language).language().eq(result_language))
-    }
+        let cli = Cli::try_parse_from(["plexity", "a.py", "-f", "tsv", "--rows", "nodes"]).unwrap();
+        assert_eq!(cli.analyze.format.format, Some(Format::Tsv));
+        assert_eq!(cli.analyze.rows, Rows::Nodes);
+        assert!(
+            Cli::try_parse_from(["plexity", "a.py", "--trace", "--trace-format", "csv"]).is_err()
+        );
 
-    #[test]
-    fn test_select_parser_python() {
-        let input_language = "python".to_string();
-        let result_language = &Some(tree_sitter_python::language());
-        assert!(select_parser(input_language).language().eq(result_language))
+        let cli = [
+            "plexity",
+            "tree",
+            "a.py",
+            "-f",
+            "dot",
+            "--named-only",
+            "--lines",
+            "3-",
+        ];
+        match Cli::try_parse_from(cli).unwrap().command {
+            Some(Command::Tree(args)) => {
+                assert_eq!(args.format, TreeFormat::Dot);
+                assert!(args.named_only);
+                assert_eq!(args.max_depth, None);
+                assert_eq!(args.lines, Some("3-".parse().unwrap()));
+            }
+            _ => panic!("expected the tree subcommand"),
+        }
     }
 
     #[test]
-    fn test_select_parser_rust() {
-        let input_language = "rust".to_string();
-        let result_language = &Some(tree_sitter_rust::language());
-        assert!(select_parser(input_language).language().eq(result_language))
+    fn test_flags_override_configuration() {
+        let config = Config {
+            format: Some(Format::Json),
+            ..Default::default()
+        };
+        assert_eq!(output_format(None, &config), Format::Json);
+        assert_eq!(output_format(Some(Format::Text), &config), Format::Text);
+        assert_eq!(output_format(None, &Config::default()), Format::Text);
+
+        let cli = Cli::try_parse_from(["plexity", "a.py", "--metrics", "cyclomatic,halstead"]);
+        let metrics = cli.unwrap().analyze.metrics;
+        assert_eq!(metrics, Some(vec![Metric::Cyclomatic, Metric::Halstead]));
+        assert!(Cli::try_parse_from(["plexity", "a.py", "--metrics", "depth"]).is_err());
     }
 
     #[test]
-    fn test_select_parser_toml() {
-        let input_language = "toml".to_string();
-        let result_language = &Some(tree_sitter_toml::language());
-        assert!(select_parser(input_language).language().eq(result_language))
+    fn test_hotspots_default_count() {
+        let cli = Cli::try_parse_from(["plexity", "a.py", "--hotspots"]).unwrap();
+        assert_eq!(
+            cli.analyze.hotspot_count(),
+            Some(hotspots::DEFAULT_HOTSPOT_COUNT)
+        );
+        let cli = Cli::try_parse_from(["plexity", "--hotspots=2", "a.py"]).unwrap();
+        assert_eq!(cli.analyze.hotspot_count(), Some(2));
+        // A count must be attached, so the path after the flag is not taken as one
+        let cli = Cli::try_parse_from(["plexity", "--hotspots", "a.py"]).unwrap();
+        assert_eq!(cli.analyze.paths, ["a.py"]);
+        assert_eq!(
+            cli.analyze.hotspot_count(),
+            Some(hotspots::DEFAULT_HOTSPOT_COUNT)
+        );
+        assert!(Cli::try_parse_from(["plexity", "a.py"])
+            .unwrap()
+            .analyze
+            .hotspot_count()
+            .is_none());
     }
 }
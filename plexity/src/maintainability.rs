@@ -0,0 +1,117 @@
+/*  A module for combining Halstead volume, cyclomatic complexity and
+source lines of code into a Maintainability Index, along with a variant
+that uses plexity's average depth in place of cyclomatic complexity.  */
+
+use crate::report::Metrics;
+use crate::suppress::Suppressions;
+use serde::Serialize;
+use std::collections::HashSet;
+use tree_sitter::Node;
+
+/// The Maintainability Index of a syntax tree or one of its subtrees.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Maintainability {
+    /// 171 - 5.2 ln(volume) - 0.23 cyclomatic - 16.2 ln(source lines)
+    pub classic: f64,
+    /// The classic index rescaled to 0-100, as reported by Visual Studio.
+    pub normalized: f64,
+    /// The classic index with average depth in place of cyclomatic complexity.
+    pub depth_classic: f64,
+    /// The depth-based index rescaled to 0-100.
+    pub depth_normalized: f64,
+}
+
+/// Counts the lines below `node` that hold code rather than only
+/// whitespace or comments, leaving out code covered by `suppressions`.
+pub fn source_lines(node: Node, suppressions: &Suppressions) -> usize {
+    let mut lines = HashSet::new();
+    collect_lines(node, suppressions, &mut lines);
+    lines.len()
+}
+
+fn collect_lines(node: Node, suppressions: &Suppressions, lines: &mut HashSet<usize>) {
+    for i in 0..node.child_count() {
+        let child = node.child(i).unwrap();
+        if suppressions.covers(child) || (child.is_extra() && child.kind().contains("comment")) {
+            continue;
+        }
+        if child.child_count() == 0 {
+            let range = child.range();
+            if range.start_byte < range.end_byte {
+                lines.extend(range.start_point.row..=range.end_point.row);
+            }
+        } else {
+            collect_lines(child, suppressions, lines);
+        }
+    }
+}
+
+/// Calculates the Maintainability Index from `metrics`, which must already
+/// hold a Halstead volume, a cyclomatic complexity and a line count.
+pub fn maintainability(metrics: &Metrics) -> Option<Maintainability> {
+    let volume = metrics.halstead.as_ref()?.volume;
+    let cyclomatic_complexity = metrics.cyclomatic_complexity? as f64;
+
+    // Guard the logarithms against empty code
+    let base =
+        171.0 - 5.2 * volume.max(1.0).ln() - 16.2 * (metrics.source_lines.max(1) as f64).ln();
+    let classic = base - 0.23 * cyclomatic_complexity;
+    let depth_classic = base - 0.23 * metrics.average_depth;
+
+    Some(Maintainability {
+        classic,
+        normalized: normalize(classic),
+        depth_classic,
+        depth_normalized: normalize(depth_classic),
+    })
+}
+
+fn normalize(index: f64) -> f64 {
+    (index * 100.0 / 171.0).max(0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::halstead::Halstead;
+    use crate::language::{select_parser, Language};
+
+    #[test]
+    fn test_source_lines_skip_blanks_and_comments() {
+        let source = "# heading\n\nx = 1\n\ny = \"\"\"a\nb\"\"\"  # trailing\n";
+        let tree = select_parser(Language::Python)
+            .unwrap()
+            .parse(source, None)
+            .unwrap();
+        assert_eq!(source_lines(tree.root_node(), &Suppressions::default()), 3);
+    }
+
+    #[test]
+    fn test_maintainability_formulas() {
+        let metrics = Metrics {
+            average_depth: 3.0,
+            cyclomatic_complexity: Some(10),
+            source_lines: 100,
+            halstead: Some(Halstead {
+                volume: 1000.0,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let index = maintainability(&metrics).unwrap();
+        let base = 171.0 - 5.2 * 1000f64.ln() - 16.2 * 100f64.ln();
+        assert!((index.classic - (base - 2.3)).abs() < 1e-9);
+        assert!((index.depth_classic - (base - 0.69)).abs() < 1e-9);
+        assert!((index.normalized - index.classic * 100.0 / 171.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_normalized_is_clamped_at_zero() {
+        assert_eq!(normalize(-20.0), 0.0);
+    }
+
+    #[test]
+    fn test_maintainability_needs_halstead_and_cyclomatic() {
+        assert_eq!(maintainability(&Metrics::default()), None);
+    }
+}
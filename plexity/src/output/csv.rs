@@ -0,0 +1,294 @@
+/*  A module for rendering results as comma- or tab-separated values, with a
+header row naming each column. Columns always come in the order listed
+here, so that scripts can rely on them. Fields holding the separator, a
+quote or a line break are quoted, with any quotes doubled, and missing
+values are left empty.  */
+
+use crate::check::Violation;
+use crate::history::Sample;
+use crate::project::FileReport;
+use std::fmt::Write;
+
+/// The columns of a table with one row per file.
+pub const FILE_COLUMNS: [&str; 7] = [
+    "file",
+    "language",
+    "node_count",
+    "maximum_depth",
+    "combined_depth",
+    "average_depth",
+    "cyclomatic_complexity",
+];
+
+/// The columns of a table with one row per function.
+pub const FUNCTION_COLUMNS: [&str; 10] = [
+    "file",
+    "language",
+    "function",
+    "start_line",
+    "end_line",
+    "node_count",
+    "maximum_depth",
+    "combined_depth",
+    "average_depth",
+    "cyclomatic_complexity",
+];
+
+/// The columns of a table with one row per node.
+pub const NODE_COLUMNS: [&str; 10] = [
+    "file",
+    "language",
+    "index",
+    "kind",
+    "depth",
+    "start_line",
+    "start_column",
+    "end_line",
+    "end_column",
+    "cyclomatic",
+];
+
+/// The columns of a table with one row per violation.
+pub const VIOLATION_COLUMNS: [&str; 7] = [
+    "file", "function", "metric", "value", "limit", "line", "column",
+];
+
+/// The columns of a history, in order.
+pub const HISTORY_COLUMNS: [&str; 8] = [
+    "commit",
+    "date",
+    "file_count",
+    "node_count",
+    "maximum_depth",
+    "average_depth",
+    "cyclomatic_complexity",
+    "subject",
+];
+
+// Rows of fields, written out with a separator between fields
+struct Table {
+    separator: char,
+    out: String,
+}
+
+impl Table {
+    fn new(separator: char, columns: &[&str]) -> Table {
+        let mut table = Table {
+            separator,
+            out: String::new(),
+        };
+        let header: Vec<String> = columns.iter().map(|column| column.to_string()).collect();
+        table.row(&header);
+        table
+    }
+
+    fn row(&mut self, fields: &[String]) {
+        for (i, field) in fields.iter().enumerate() {
+            if i > 0 {
+                self.out.push(self.separator);
+            }
+            self.out.push_str(&quote(field, self.separator));
+        }
+        writeln!(self.out).unwrap();
+    }
+}
+
+/// Renders one row for each file.
+pub fn files(files: &[FileReport], separator: char) -> String {
+    let mut table = Table::new(separator, &FILE_COLUMNS);
+    for file in files {
+        let metrics = &file.report.metrics;
+        table.row(&[
+            file.path.clone(),
+            file.language.to_string(),
+            metrics.node_count.to_string(),
+            metrics.maximum_depth.to_string(),
+            metrics.combined_depth.to_string(),
+            decimal(metrics.average_depth),
+            optional(metrics.cyclomatic_complexity),
+        ]);
+    }
+    table.out
+}
+
+/// Renders one row for each function in each file, with the same metrics
+/// as for files.
+pub fn functions(files: &[FileReport], separator: char) -> String {
+    let mut table = Table::new(separator, &FUNCTION_COLUMNS);
+    for file in files {
+        for function in &file.report.functions {
+            let metrics = &function.metrics;
+            table.row(&[
+                file.path.clone(),
+                file.language.to_string(),
+                function.name.clone(),
+                function.span.start.line.to_string(),
+                function.span.end.line.to_string(),
+                metrics.node_count.to_string(),
+                metrics.maximum_depth.to_string(),
+                metrics.combined_depth.to_string(),
+                decimal(metrics.average_depth),
+                optional(metrics.cyclomatic_complexity),
+            ]);
+        }
+    }
+    table.out
+}
+
+/// Renders one row for each node of each file, for the files whose nodes
+/// were kept.
+pub fn nodes(files: &[FileReport], separator: char) -> String {
+    let mut table = Table::new(separator, &NODE_COLUMNS);
+    for file in files {
+        for node in file.nodes.iter().flatten() {
+            table.row(&[
+                file.path.clone(),
+                file.language.to_string(),
+                node.index.to_string(),
+                node.kind.clone(),
+                node.depth.to_string(),
+                node.span.start.line.to_string(),
+                node.span.start.column.to_string(),
+                node.span.end.line.to_string(),
+                node.span.end.column.to_string(),
+                node.cyclomatic.to_string(),
+            ]);
+        }
+    }
+    table.out
+}
+
+/// Renders one row for each exceeded limit.
+pub fn violations(violations: &[Violation], separator: char) -> String {
+    let mut table = Table::new(separator, &VIOLATION_COLUMNS);
+    for violation in violations {
+        table.row(&[
+            violation.path.clone(),
+            violation.function.clone().unwrap_or_default(),
+            violation.metric.to_string(),
+            decimal(violation.value),
+            decimal(violation.limit),
+            violation.location.line.to_string(),
+            violation.location.column.to_string(),
+        ]);
+    }
+    table.out
+}
+
+/// Renders a history as one row per sampled commit, oldest first.
+pub fn history(samples: &[Sample], separator: char) -> String {
+    let mut table = Table::new(separator, &HISTORY_COLUMNS);
+    for sample in samples {
+        table.row(&[
+            sample.commit.clone(),
+            sample.date.clone(),
+            sample.file_count.to_string(),
+            sample.node_count.to_string(),
+            sample.maximum_depth.to_string(),
+            decimal(sample.average_depth),
+            optional(sample.cyclomatic_complexity),
+            sample.subject.clone(),
+        ]);
+    }
+    table.out
+}
+
+// Quotes a field when it would otherwise be split or misread
+fn quote(value: &str, separator: char) -> String {
+    if value.contains([separator, '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn decimal(value: f64) -> String {
+    format!("{value:.4}")
+}
+
+fn optional(value: Option<usize>) -> String {
+    value.map(|value| value.to_string()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::language::Language;
+    use crate::project::file_report;
+    use crate::report::Position;
+
+    #[test]
+    fn test_quoting() {
+        assert_eq!(quote("plain", ','), "plain");
+        assert_eq!(quote("a, b", ','), "\"a, b\"");
+        assert_eq!(quote("a, b", '\t'), "a, b");
+        assert_eq!(quote("a\tb", '\t'), "\"a\tb\"");
+        assert_eq!(quote("say \"hi\"", ','), "\"say \"\"hi\"\"\"");
+        assert_eq!(quote("two\nlines", '\t'), "\"two\nlines\"");
+    }
+
+    #[test]
+    fn test_files_and_functions() {
+        let source = "def f(a):\n    if a:\n        return 1\n";
+        let reports = [file_report("src/a, b.py", source, Language::Python)];
+        let metrics = &reports[0].report.metrics;
+
+        let out = files(&reports, ',');
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines[0], FILE_COLUMNS.join(","));
+        assert_eq!(
+            lines[1],
+            format!(
+                "\"src/a, b.py\",python,{},{},{},{:.4},2",
+                metrics.node_count,
+                metrics.maximum_depth,
+                metrics.combined_depth,
+                metrics.average_depth
+            )
+        );
+
+        let out = functions(&reports, '\t');
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].ends_with("average_depth\tcyclomatic_complexity"));
+        assert!(lines[1].starts_with("src/a, b.py\tpython\tf\t1\t3\t"));
+    }
+
+    #[test]
+    fn test_violations() {
+        let violation = Violation {
+            path: "a.py".to_string(),
+            function: Some("f".to_string()),
+            metric: "average_depth",
+            value: 12.345678901234567,
+            limit: 3.0,
+            location: Position { line: 4, column: 9 },
+            span: None,
+        };
+        let out = violations(&[violation], ',');
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines[0], VIOLATION_COLUMNS.join(","));
+        assert_eq!(lines[1], "a.py,f,average_depth,12.3457,3.0000,4,9");
+    }
+
+    #[test]
+    fn test_history() {
+        let sample = Sample {
+            commit: "abc".to_string(),
+            date: "2024-01-02T03:04:05+00:00".to_string(),
+            subject: "Fix parsing, again".to_string(),
+            file_count: 2,
+            node_count: 40,
+            maximum_depth: 7,
+            average_depth: 3.5,
+            cyclomatic_complexity: None,
+        };
+        let out = history(&[sample], ',');
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines[0], HISTORY_COLUMNS.join(","));
+        assert_eq!(
+            lines[1],
+            "abc,2024-01-02T03:04:05+00:00,2,40,7,3.5000,,\"Fix parsing, again\""
+        );
+    }
+}
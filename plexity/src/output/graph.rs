@@ -0,0 +1,187 @@
+/*  A module for drawing a syntax tree as a Graphviz DOT or Mermaid graph.
+
+Each node is labelled with its kind and span, and with the source text of
+leaves, and each edge with the field that the child fills in its parent.
+Nodes are filled with a colour from pale yellow to dark red according to
+their depth, relative to the deepest node drawn. Anonymous nodes, such as
+punctuation, are drawn with a dashed outline.  */
+
+use crate::syntax::TreeNode;
+use std::fmt::Write;
+
+// The fill colours for increasing depths, from the YlOrRd scheme of
+// ColorBrewer, and the first of them dark enough to need white text
+const PALETTE: [&str; 9] = [
+    "#ffffcc", "#ffeda0", "#fed976", "#feb24c", "#fd8d3c", "#fc4e2a", "#e31a1c", "#bd0026",
+    "#800026",
+];
+const DARK: usize = 6;
+
+/// Renders the tree made up of `nodes` in the DOT language.
+pub fn dot(nodes: &[TreeNode]) -> String {
+    let deepest = deepest(nodes);
+    let mut out = String::new();
+    writeln!(out, "digraph syntax_tree {{").unwrap();
+    writeln!(
+        out,
+        "  node [shape=box, style=\"rounded,filled\", fontname=\"monospace\"];"
+    )
+    .unwrap();
+    writeln!(out, "  edge [fontname=\"monospace\", fontsize=10];").unwrap();
+    for (i, node) in nodes.iter().enumerate() {
+        let shade = shade(node.depth, deepest);
+        let style = if node.named {
+            ""
+        } else {
+            ", style=\"rounded,filled,dashed\""
+        };
+        let font = if shade >= DARK {
+            ", fontcolor=\"white\""
+        } else {
+            ""
+        };
+        writeln!(
+            out,
+            "  n{i} [label=\"{}\", fillcolor=\"{}\"{style}{font}];",
+            dot_escape(&label(node)),
+            PALETTE[shade]
+        )
+        .unwrap();
+        if let Some(parent) = node.parent {
+            match node.field {
+                Some(field) => writeln!(out, "  n{parent} -> n{i} [label=\"{field}\"];"),
+                None => writeln!(out, "  n{parent} -> n{i};"),
+            }
+            .unwrap();
+        }
+    }
+    writeln!(out, "}}").unwrap();
+    out
+}
+
+/// Renders the tree made up of `nodes` as a Mermaid flowchart.
+pub fn mermaid(nodes: &[TreeNode]) -> String {
+    let deepest = deepest(nodes);
+    let mut out = String::new();
+    writeln!(out, "flowchart TD").unwrap();
+    for (shade, colour) in PALETTE.iter().enumerate() {
+        let text = if shade >= DARK { "#fff" } else { "#000" };
+        writeln!(
+            out,
+            "  classDef depth{shade} fill:{colour},stroke:#666,color:{text}"
+        )
+        .unwrap();
+    }
+    writeln!(out, "  classDef anonymous stroke-dasharray:4 3").unwrap();
+    for (i, node) in nodes.iter().enumerate() {
+        writeln!(
+            out,
+            "  n{i}[\"{}\"]:::depth{}",
+            mermaid_escape(&label(node)).replace('\n', "<br/>"),
+            shade(node.depth, deepest)
+        )
+        .unwrap();
+        if !node.named {
+            writeln!(out, "  class n{i} anonymous").unwrap();
+        }
+        if let Some(parent) = node.parent {
+            match node.field {
+                Some(field) => writeln!(out, "  n{parent} -->|{field}| n{i}"),
+                None => writeln!(out, "  n{parent} --> n{i}"),
+            }
+            .unwrap();
+        }
+    }
+    out
+}
+
+// The kind of the node, its span and, for named leaves, its source text;
+// the text of an anonymous leaf is already its kind
+fn label(node: &TreeNode) -> String {
+    let span = &node.span;
+    let mut label = format!(
+        "{}\n{}:{}-{}:{}",
+        node.kind, span.start.line, span.start.column, span.end.line, span.end.column
+    );
+    if let Some(text) = node.text.as_ref().filter(|_| node.named) {
+        write!(label, "\n{text}").unwrap();
+    }
+    label
+}
+
+fn deepest(nodes: &[TreeNode]) -> usize {
+    nodes.iter().map(|node| node.depth).max().unwrap_or(0)
+}
+
+// Spreads depths from 0 to `deepest` across the palette
+fn shade(depth: usize, deepest: usize) -> usize {
+    if deepest == 0 {
+        return 0;
+    }
+    depth * (PALETTE.len() - 1) / deepest
+}
+
+fn dot_escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+// Mermaid reads entity codes written as `#name;` in quoted labels
+fn mermaid_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("#quot;"),
+            '#' => escaped.push_str("#35;"),
+            '<' => escaped.push_str("#lt;"),
+            '>' => escaped.push_str("#gt;"),
+            '&' => escaped.push_str("#amp;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::language::Language;
+    use crate::syntax::{Collector, Cutoff};
+
+    fn nodes(source: &str) -> Vec<TreeNode> {
+        let mut collector = Collector::new(Cutoff::default());
+        crate::analyze_with(source, Language::Python, |node, record| {
+            collector.visit(node, record, source)
+        })
+        .unwrap();
+        collector.finish()
+    }
+
+    #[test]
+    fn test_dot() {
+        let out = dot(&nodes("if a:\n    b = \"x\"\n"));
+        assert!(out.starts_with("digraph syntax_tree {\n"));
+        assert!(out.ends_with("}\n"));
+        assert!(out.contains("n0 [label=\"if_statement\\n1:1-2:12\", fillcolor=\"#ffffcc\"];"));
+        assert!(out.contains("n0 -> n2 [label=\"condition\"];"));
+        assert!(out.contains("identifier\\n1:4-1:5\\na\""));
+        // Anonymous nodes are dashed, and quotes in labels are escaped
+        assert!(out.contains(
+            "label=\"if\\n1:1-1:3\", fillcolor=\"#ffeda0\", style=\"rounded,filled,dashed\""
+        ));
+        assert!(out.contains("string_start\\n2:9-2:10\\n\\\"\""));
+    }
+
+    #[test]
+    fn test_mermaid() {
+        let out = mermaid(&nodes("if a:\n    b = \"<x>\"\n"));
+        assert!(out.starts_with("flowchart TD\n"));
+        assert!(out.contains("  n0[\"if_statement<br/>1:1-2:14\"]:::depth0\n"));
+        assert!(out.contains("  n0 -->|condition| n2\n"));
+        assert!(out.contains("  class n1 anonymous\n"));
+        assert!(out.contains("<br/>#lt;x#gt;\"]"));
+        assert!(out.contains("<br/>#quot;\"]"));
+        assert_eq!(shade(4, 4), PALETTE.len() - 1);
+    }
+}
@@ -0,0 +1,258 @@
+/*  A module for rendering reports as a single HTML page that needs nothing
+else to be viewed: every style is inlined and there are no scripts, so the
+page can be archived on its own, as the artifact of a CI run.
+
+A sidebar lists the most deeply nested regions across every file. Each file
+follows with its scorecard, collapsed, and its source code, with each line
+shaded by the depth of its deepest token. Shades are relative to the deepest
+line in the whole report, so that files can be compared at a glance.  */
+
+use super::text;
+use crate::hotspots::Hotspot;
+use crate::project::{FileReport, Summary};
+use std::fmt::Write;
+
+/// The most deeply nested regions listed in the sidebar.
+pub const SIDEBAR_COUNT: usize = 20;
+
+// The number of shades that depths are divided into
+const SHADES: usize = 10;
+
+const STYLE: &str = "
+body { margin: 0; font-family: system-ui, sans-serif; color: #222; }
+header { padding: 0.5rem 1rem; background: #333; color: #fff; }
+header h1 { margin: 0; font-size: 1.3rem; }
+.layout { display: flex; align-items: flex-start; }
+nav { position: sticky; top: 0; width: 22rem; max-height: 100vh; overflow-y: auto; padding: 0 1rem; box-sizing: border-box; border-right: 1px solid #ddd; font-size: 0.85rem; }
+nav ol, nav ul { padding-left: 1.2rem; }
+nav li { margin-bottom: 0.3rem; }
+main { flex: 1; min-width: 0; padding: 0 1rem 2rem; }
+section { margin-top: 1.5rem; }
+h2 { font-size: 1.1rem; }
+.language, .kind { color: #777; font-weight: normal; }
+details { margin-bottom: 0.5rem; }
+summary { cursor: pointer; }
+pre { background: #f6f6f6; padding: 0.5rem; overflow-x: auto; }
+.legend span { display: inline-block; min-width: 1.8rem; text-align: center; }
+table.source { border-collapse: collapse; width: 100%; font-family: ui-monospace, monospace; font-size: 0.8rem; }
+table.source td { padding: 0 0.4rem; vertical-align: top; }
+td.line, td.depth { text-align: right; color: #888; user-select: none; }
+td.line a { color: inherit; text-decoration: none; }
+td.code { white-space: pre; width: 100%; }
+tr:target { outline: 2px solid #36c; }
+";
+
+/// Renders the reports for every file, and their summary, as an HTML page.
+/// Source code is only shown for files with a heatmap.
+pub fn report(files: &[FileReport], summary: &Summary) -> String {
+    let deepest = files
+        .iter()
+        .filter_map(|file| file.heatmap.as_ref())
+        .flat_map(|heatmap| heatmap.line_depths.iter().copied())
+        .max()
+        .unwrap_or(0)
+        .max(1);
+
+    let mut out = String::new();
+    writeln!(out, "<!DOCTYPE html>").unwrap();
+    writeln!(out, "<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">").unwrap();
+    writeln!(out, "<title>Plexity report</title>").unwrap();
+    writeln!(out, "<style>{STYLE}").unwrap();
+    for shade in 1..=SHADES {
+        let hue = 55 - 55 * (shade - 1) / (SHADES - 1);
+        let lightness = 92 - 3 * shade;
+        writeln!(
+            out,
+            ".s{shade} {{ background: hsl({hue}, 95%, {lightness}%); }}"
+        )
+        .unwrap();
+    }
+    writeln!(out, "</style>\n</head>\n<body>").unwrap();
+    writeln!(
+        out,
+        "<header><h1>Plexity report</h1>{} file(s), maximum depth {}, average depth {:.2}</header>",
+        summary.file_count, summary.maximum_depth, summary.average_depth
+    )
+    .unwrap();
+
+    writeln!(out, "<div class=\"layout\">").unwrap();
+    sidebar(&mut out, files);
+    writeln!(out, "<main>").unwrap();
+    writeln!(
+        out,
+        "<details><summary>Project summary</summary><pre>{}</pre></details>",
+        escape(&text::summary(summary))
+    )
+    .unwrap();
+    legend(&mut out, deepest);
+    for (index, file) in files.iter().enumerate() {
+        file_section(&mut out, index, file, deepest);
+    }
+    writeln!(out, "</main>\n</div>\n</body>\n</html>").unwrap();
+    out
+}
+
+fn sidebar(out: &mut String, files: &[FileReport]) {
+    let mut hotspots: Vec<(usize, &FileReport, &Hotspot)> = files
+        .iter()
+        .enumerate()
+        .filter_map(|(index, file)| Some((index, file, file.heatmap.as_ref()?)))
+        .flat_map(|(index, file, heatmap)| {
+            heatmap
+                .hotspots
+                .iter()
+                .map(move |hotspot| (index, file, hotspot))
+        })
+        .collect();
+    // Sorting is stable, so regions of the same depth stay in file order
+    hotspots.sort_by_key(|(_, _, hotspot)| std::cmp::Reverse(hotspot.depth));
+
+    writeln!(out, "<nav>\n<h2>Deepest regions</h2>\n<ol>").unwrap();
+    for (index, file, hotspot) in hotspots.into_iter().take(SIDEBAR_COUNT) {
+        let line = hotspot.span.start.line;
+        writeln!(
+            out,
+            "<li><a href=\"#{}\">{}:{line}</a> <span class=\"kind\">{}</span>, depth {}</li>",
+            line_id(index, line),
+            escape(&file.path),
+            escape(&hotspot.kind),
+            hotspot.depth
+        )
+        .unwrap();
+    }
+    writeln!(out, "</ol>\n<h2>Files</h2>\n<ul>").unwrap();
+    for (index, file) in files.iter().enumerate() {
+        writeln!(
+            out,
+            "<li><a href=\"#f{index}\">{}</a> (maximum depth {})</li>",
+            escape(&file.path),
+            file.report.metrics.maximum_depth
+        )
+        .unwrap();
+    }
+    writeln!(out, "</ul>\n</nav>").unwrap();
+}
+
+// A key to the shades, each labelled with the lowest depth it stands for
+fn legend(out: &mut String, deepest: usize) {
+    write!(out, "<p class=\"legend\">Depth: ").unwrap();
+    let mut previous = 0;
+    for depth in 1..=deepest {
+        let shade = shade(depth, deepest);
+        if shade != previous {
+            write!(out, "<span class=\"s{shade}\">{depth}</span>").unwrap();
+            previous = shade;
+        }
+    }
+    writeln!(out, "</p>").unwrap();
+}
+
+fn file_section(out: &mut String, index: usize, file: &FileReport, deepest: usize) {
+    let metrics = &file.report.metrics;
+    writeln!(
+        out,
+        "<section id=\"f{index}\">\n<h2>{} <span class=\"language\">{}</span></h2>",
+        escape(&file.path),
+        file.language
+    )
+    .unwrap();
+    writeln!(
+        out,
+        "<details><summary>Scorecard: {} nodes, maximum depth {}, average depth {:.2}</summary><pre>{}</pre></details>",
+        metrics.node_count,
+        metrics.maximum_depth,
+        metrics.average_depth,
+        escape(&text::scorecard(&file.report))
+    )
+    .unwrap();
+
+    if let Some(heatmap) = &file.heatmap {
+        writeln!(out, "<table class=\"source\">").unwrap();
+        for (number, (line, &depth)) in heatmap.source.lines().zip(&heatmap.line_depths).enumerate()
+        {
+            let id = line_id(index, number + 1);
+            writeln!(
+                out,
+                "<tr id=\"{id}\" class=\"s{}\" title=\"depth {depth}\"><td class=\"line\"><a href=\"#{id}\">{}</a></td><td class=\"depth\">{}</td><td class=\"code\">{}</td></tr>",
+                shade(depth, deepest),
+                number + 1,
+                if depth > 0 { depth.to_string() } else { String::new() },
+                escape(line)
+            )
+            .unwrap();
+        }
+        writeln!(out, "</table>").unwrap();
+    }
+    writeln!(out, "</section>").unwrap();
+}
+
+fn line_id(file: usize, line: usize) -> String {
+    format!("f{file}-{line}")
+}
+
+// The shade of a line, from 0 for lines without code up to `SHADES` for
+// the deepest lines
+fn shade(depth: usize, deepest: usize) -> usize {
+    (depth * SHADES).div_ceil(deepest).min(SHADES)
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::heatmap::Collector;
+    use crate::language::Language;
+    use crate::project::summarize;
+
+    fn file_report(path: &str, source: &str) -> FileReport {
+        let mut collector = Collector::default();
+        let report = crate::analyze_with(source, Language::Python, |node, record| {
+            collector.visit(node, record, source)
+        })
+        .unwrap();
+        FileReport {
+            heatmap: Some(collector.finish(source)),
+            ..FileReport::new(path.to_string(), Language::Python, report)
+        }
+    }
+
+    #[test]
+    fn test_report_is_self_contained() {
+        let files = [
+            file_report("a.py", "if a < b:\n    print(\"<b>\")\n"),
+            file_report("b&c.py", "x = 1\n"),
+        ];
+        let page = report(&files, &summarize(&files));
+        assert!(page.starts_with("<!DOCTYPE html>"));
+        assert!(!page.contains("<script") && !page.contains("<link"));
+        assert!(!page.contains("http"));
+        // Source code is escaped, and the deepest line takes the last shade
+        assert!(page.contains("print(&quot;&lt;b&gt;&quot;)"));
+        assert!(page.contains("<tr id=\"f0-2\" class=\"s10\""));
+        assert!(page.contains("<a href=\"#f0-2\">a.py:2</a>"));
+        assert!(page.contains("b&amp;c.py"));
+        assert_eq!(page.matches("<details>").count(), 3);
+    }
+
+    #[test]
+    fn test_shades() {
+        assert_eq!(shade(0, 7), 0);
+        assert_eq!(shade(1, 7), 2);
+        assert_eq!(shade(7, 7), SHADES);
+        assert_eq!(shade(3, 30), 1);
+    }
+}
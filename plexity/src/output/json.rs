@@ -0,0 +1,280 @@
+/*  A module for rendering a report as a machine-readable JSON document.
+
+Each analysed file produces one document with the following fields:
+
+  - `schema_version`: the version of this layout, currently 1
+  - `file`: the path of the analysed file, as it was given
+  - `language`: the name of the grammar used to parse the file
+  - `node_count`, `maximum_depth`, `combined_depth`, `average_depth`
+  - `suppressed_nodes`: the number of nodes left out by suppression
+    comments such as `# plexity: ignore-next`
+  - `deepest_node`: the `span` of the first of the most deeply nested
+    nodes, or null when the tree is empty
+  - `cyclomatic_complexity`, `cognitive_complexity`: integers, or null
+    where they are not calculated for the language
+  - `halstead`: null, or an object holding the `distinct_operators`,
+    `distinct_operands`, `total_operators`, `total_operands`, `vocabulary`,
+    `length`, `volume`, `difficulty`, `effort` and `estimated_bugs`
+  - `source_lines`: the number of lines holding code
+  - `maintainability`: null, or an object holding the `classic` and
+    `normalized` (0-100) Maintainability Index, and the `depth_classic` and
+    `depth_normalized` variants that use the average depth in place of the
+    cyclomatic complexity
+  - `functions`: one object per function or method, holding its qualified
+    `name`, its `span` and the same metrics as above, measured from the
+    function itself
+  - `nodes`: only present when requested; one object per node holding its
+    `index`, `depth`, `kind`, `span` and whether it is `cyclomatic`
+  - `hotspots`: only present when requested; the most deeply nested
+    regions, deepest first, each holding the `kind` of the construct, its
+    `span`, the `depth` of its deepest node and a source `snippet`
+
+Every `span` holds one-based `line` and `column` numbers for its `start`
+and `end`.
+
+When several files are analysed at once, a single project document is
+produced instead. It holds the `schema_version`, a `files` array with one
+object per file (laid out as above, without their own `schema_version`)
+and a `summary` with the `file_count`, `function_count`, `node_count`,
+`source_lines` and `combined_depth` totals, the overall `maximum_depth`
+and `average_depth`, the `mean_cyclomatic_complexity`,
+`mean_cognitive_complexity` and `mean_maintainability` (each null when no
+file has that metric) and the `worst_files`, a list of the most deeply
+nested files with their `file`, `maximum_depth`, `average_depth` and
+`cyclomatic_complexity`.
+
+Comparing two files produces a document holding the `schema_version` and
+the `before` and `after` files, laid out as above. Checking files against
+limits produces a document holding the `schema_version`, the number of
+`files_checked` and a `violations` array, with one object per exceeded
+limit holding the `file`, the qualified name of the `function` (null when
+the limit applies to the whole file), the `metric`, its `value`, the
+`limit`, the `location` (a `line` and `column`) of the deepest node or
+of the start of the function or file, and the `span` of the deepest node
+or of the function (null for limits on a whole file). When checking
+against a baseline, only the violations that are new or worse than the
+baseline are listed.
+
+Diffing two revisions produces a document holding the `schema_version`,
+the `base` and `head` revisions and a `files` array, with one object per
+changed source file holding its `file` path (and `old_path` when it was
+renamed), its `status` (`added`, `modified` or `renamed`), its `language`,
+the metrics of the file `before` (null when it was added) and `after` the
+change, and the `functions` that were added or modified, each holding its
+`name`, `status`, `span` in the newer version and its metrics `before` and
+`after`.
+
+Following the history of a path produces a document holding the
+`schema_version`, the `path` and a `commits` array, oldest first, with one
+object per sampled commit holding its `commit` hash, committer `date`,
+`subject`, the `file_count` and `node_count` totals, the overall
+`maximum_depth` and `average_depth` and the `cyclomatic_complexity` summed
+over the files (null when no file has it).
+
+Fields may be added without changing the version; removing or changing
+the meaning of a field requires a new `SCHEMA_VERSION`.  */
+
+use crate::check::Violation;
+use crate::diff::FileDelta;
+use crate::history::Sample;
+use crate::project::{FileReport, Summary};
+use serde::Serialize;
+
+/// The version of the JSON document layout described above.
+pub const SCHEMA_VERSION: u32 = 1;
+
+#[derive(Serialize)]
+struct Document<'a> {
+    schema_version: u32,
+    #[serde(flatten)]
+    file: &'a FileReport,
+}
+
+#[derive(Serialize)]
+struct ProjectDocument<'a> {
+    schema_version: u32,
+    files: &'a [FileReport],
+    summary: &'a Summary,
+}
+
+#[derive(Serialize)]
+struct ComparisonDocument<'a> {
+    schema_version: u32,
+    before: &'a FileReport,
+    after: &'a FileReport,
+}
+
+#[derive(Serialize)]
+struct DiffDocument<'a> {
+    schema_version: u32,
+    base: &'a str,
+    head: &'a str,
+    files: &'a [FileDelta],
+}
+
+#[derive(Serialize)]
+struct HistoryDocument<'a> {
+    schema_version: u32,
+    path: &'a str,
+    commits: &'a [Sample],
+}
+
+#[derive(Serialize)]
+struct CheckDocument<'a> {
+    schema_version: u32,
+    files_checked: usize,
+    violations: &'a [Violation],
+}
+
+/// Renders the report for a single file as a JSON document.
+pub fn document(file: &FileReport) -> String {
+    let document = Document {
+        schema_version: SCHEMA_VERSION,
+        file,
+    };
+    serde_json::to_string_pretty(&document).unwrap()
+}
+
+/// Renders the reports for several files, and their summary, as a single
+/// JSON document.
+pub fn project_document(files: &[FileReport], summary: &Summary) -> String {
+    let document = ProjectDocument {
+        schema_version: SCHEMA_VERSION,
+        files,
+        summary,
+    };
+    serde_json::to_string_pretty(&document).unwrap()
+}
+
+/// Renders the reports for two files that are being compared.
+pub fn comparison_document(before: &FileReport, after: &FileReport) -> String {
+    let document = ComparisonDocument {
+        schema_version: SCHEMA_VERSION,
+        before,
+        after,
+    };
+    serde_json::to_string_pretty(&document).unwrap()
+}
+
+/// Renders the outcome of checking `files_checked` files against limits.
+pub fn check_document(files_checked: usize, violations: &[Violation]) -> String {
+    let document = CheckDocument {
+        schema_version: SCHEMA_VERSION,
+        files_checked,
+        violations,
+    };
+    serde_json::to_string_pretty(&document).unwrap()
+}
+
+/// Renders how the files changed from revision `base` to `head` moved.
+pub fn diff_document(base: &str, head: &str, files: &[FileDelta]) -> String {
+    let document = DiffDocument {
+        schema_version: SCHEMA_VERSION,
+        base,
+        head,
+        files,
+    };
+    serde_json::to_string_pretty(&document).unwrap()
+}
+
+/// Renders the metrics of `path` at each sampled commit.
+pub fn history_document(path: &str, commits: &[Sample]) -> String {
+    let document = HistoryDocument {
+        schema_version: SCHEMA_VERSION,
+        path,
+        commits,
+    };
+    serde_json::to_string_pretty(&document).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hotspots::Hotspot;
+    use crate::language::Language;
+    use crate::project::{file_report, summarize};
+    use crate::report::{NodeRecord, Position};
+    use serde_json::Value;
+
+    #[test]
+    fn test_document_fields() {
+        let file = file_report("hello.py", "print(\"Hello world\")", Language::Python);
+        let value: Value = serde_json::from_str(&document(&file)).unwrap();
+        assert_eq!(value["schema_version"], SCHEMA_VERSION);
+        assert_eq!(value["file"], "hello.py");
+        assert_eq!(value["language"], "python");
+        assert_eq!(value["node_count"], 10);
+        assert_eq!(value["cyclomatic_complexity"], 1);
+        assert_eq!(value["functions"], serde_json::json!([]));
+        assert!(value.get("nodes").is_none());
+    }
+
+    #[test]
+    fn test_document_with_nodes() {
+        let mut file = file_report("a.json", "{}", Language::Json);
+        file.nodes = Some(vec![NodeRecord {
+            index: 1,
+            depth: 0,
+            kind: "object".to_string(),
+            span: Default::default(),
+            cyclomatic: false,
+        }]);
+        let value: Value = serde_json::from_str(&document(&file)).unwrap();
+        assert_eq!(value["cyclomatic_complexity"], Value::Null);
+        assert_eq!(value["halstead"], Value::Null);
+        assert_eq!(value["nodes"][0]["kind"], "object");
+        assert_eq!(value["nodes"][0]["span"]["start"]["line"], 0);
+    }
+
+    #[test]
+    fn test_project_document() {
+        let files = vec![
+            file_report("a.py", "x = 1", Language::Python),
+            file_report("b.py", "y = [1]", Language::Python),
+        ];
+        let value: Value =
+            serde_json::from_str(&project_document(&files, &summarize(&files))).unwrap();
+        assert_eq!(value["schema_version"], SCHEMA_VERSION);
+        assert_eq!(value["files"][1]["file"], "b.py");
+        assert!(value["files"][0].get("schema_version").is_none());
+        assert_eq!(value["summary"]["file_count"], 2);
+        assert_eq!(value["summary"]["worst_files"][0]["file"], "b.py");
+    }
+
+    #[test]
+    fn test_check_document() {
+        let violations = vec![Violation {
+            path: "a.py".to_string(),
+            function: Some("Tree.walk".to_string()),
+            metric: "maximum_depth",
+            value: 12.0,
+            limit: 10.0,
+            location: Position {
+                line: 7,
+                column: 13,
+            },
+            span: None,
+        }];
+        let value: Value = serde_json::from_str(&check_document(3, &violations)).unwrap();
+        assert_eq!(value["files_checked"], 3);
+        assert_eq!(value["violations"][0]["file"], "a.py");
+        assert_eq!(value["violations"][0]["metric"], "maximum_depth");
+        assert_eq!(value["violations"][0]["limit"], 10.0);
+        assert_eq!(value["violations"][0]["function"], "Tree.walk");
+        assert_eq!(value["violations"][0]["location"]["line"], 7);
+    }
+
+    #[test]
+    fn test_document_with_hotspots() {
+        let mut file = file_report("a.py", "x = [[1]]", Language::Python);
+        file.hotspots = Some(vec![Hotspot {
+            kind: "assignment".to_string(),
+            span: Default::default(),
+            depth: 4,
+            snippet: "x = [[1]]".to_string(),
+        }]);
+        let value: Value = serde_json::from_str(&document(&file)).unwrap();
+        assert_eq!(value["hotspots"][0]["depth"], 4);
+        assert_eq!(value["hotspots"][0]["snippet"], "x = [[1]]");
+    }
+}
@@ -0,0 +1,63 @@
+/*  A module for rendering plexity reports in the supported output formats.  */
+
+pub mod csv;
+pub mod graph;
+pub mod html;
+pub mod json;
+pub mod sarif;
+pub mod text;
+
+/// The formats that a report can be written in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Format {
+    /// The human-readable "PLEXITY SCORECARD".
+    #[default]
+    Text,
+    /// One JSON document per analysed file, following `json::SCHEMA_VERSION`.
+    Json,
+    /// Comma-separated values, with one row per file, function or node.
+    Csv,
+    /// Tab-separated values, laid out like `Csv`.
+    Tsv,
+}
+
+impl Format {
+    /// Every supported output format.
+    pub const ALL: [Format; 4] = [Format::Text, Format::Json, Format::Csv, Format::Tsv];
+
+    /// The name used to select this format on the command line.
+    pub fn name(self) -> &'static str {
+        match self {
+            Format::Text => "text",
+            Format::Json => "json",
+            Format::Csv => "csv",
+            Format::Tsv => "tsv",
+        }
+    }
+
+    /// The character between fields, for the tabular formats.
+    pub fn separator(self) -> Option<char> {
+        match self {
+            Format::Csv => Some(','),
+            Format::Tsv => Some('\t'),
+            Format::Text | Format::Json => None,
+        }
+    }
+
+    /// Looks up a format by the name used on the command line.
+    pub fn from_name(name: &str) -> Option<Format> {
+        Format::ALL
+            .iter()
+            .find(|format| format.name() == name)
+            .copied()
+    }
+}
+
+// Whole numbers are shown without decimal places
+pub(crate) fn number(value: f64) -> String {
+    if value.fract() == 0.0 {
+        format!("{value:.0}")
+    } else {
+        format!("{value:.2}")
+    }
+}
@@ -0,0 +1,444 @@
+/*  A module for rendering the violations found by `check` as a SARIF 2.1.0
+log, the format read by code-scanning dashboards.
+
+The log holds a single run, whose tool lists one rule per checked metric.
+Each violation becomes a result naming its rule, with a level of `warning`,
+or `error` once the value reaches twice the limit, and a physical location
+covering the deepest node (for depth limits) or the function. Limits on a
+whole file point at its first line. Lines and columns are one-based.
+Columns count bytes everywhere else in plexity, but are converted here to
+UTF-16 code units, the `columnKind` that SARIF viewers expect, and are left
+out when the source of the file is not at hand.  */
+
+use super::number;
+use crate::check::Violation;
+use crate::report::{Position, Span};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// The version of SARIF that logs are written in.
+pub const VERSION: &str = "2.1.0";
+
+/// The schema that logs follow.
+pub const SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+// Each metric that can be checked, as named in violations, with its rule
+// ID, rule name and a description of the rule
+const RULES: [(&str, &str, &str, &str); 4] = [
+    (
+        "maximum_depth",
+        "maximum-depth",
+        "MaximumDepth",
+        "Syntax tree nested too deeply",
+    ),
+    (
+        "average_depth",
+        "average-depth",
+        "AverageDepth",
+        "Average syntax tree depth too high",
+    ),
+    (
+        "cyclomatic_complexity",
+        "cyclomatic-complexity",
+        "CyclomaticComplexity",
+        "Cyclomatic complexity too high",
+    ),
+    (
+        "cognitive_complexity",
+        "cognitive-complexity",
+        "CognitiveComplexity",
+        "Cognitive complexity too high",
+    ),
+];
+
+// The help text of each rule, in the same order
+const HELP: [&str; 4] = [
+    "The deepest node in the syntax tree is nested further than the limit allows. Flatten the code by returning early, extracting helper functions or naming intermediate values.",
+    "The nodes of the syntax tree are, on average, nested further than the limit allows. Split long chains of nested expressions and deeply indented blocks into smaller pieces.",
+    "There are more independent paths through the code than the limit allows. Split the code into smaller functions, or replace chains of conditions with lookups.",
+    "The code has more nested and interrupted control flow than the limit allows. Reduce nesting and break the logic into functions that each do one thing.",
+];
+
+#[derive(Serialize)]
+struct Log<'a> {
+    #[serde(rename = "$schema")]
+    schema: &'static str,
+    version: &'static str,
+    runs: [Run<'a>; 1],
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Run<'a> {
+    tool: Tool,
+    column_kind: &'static str,
+    results: Vec<SarifResult<'a>>,
+}
+
+#[derive(Serialize)]
+struct Tool {
+    driver: Driver,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Driver {
+    name: &'static str,
+    version: &'static str,
+    rules: Vec<Rule>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Rule {
+    id: &'static str,
+    name: &'static str,
+    short_description: Message,
+    help: Message,
+    default_configuration: Configuration,
+}
+
+#[derive(Serialize)]
+struct Configuration {
+    level: &'static str,
+}
+
+#[derive(Serialize)]
+struct Message {
+    text: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifResult<'a> {
+    rule_id: &'static str,
+    rule_index: usize,
+    level: &'static str,
+    message: Message,
+    locations: [Location<'a>; 1],
+    properties: Properties,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Location<'a> {
+    physical_location: PhysicalLocation,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    logical_locations: Vec<LogicalLocation<'a>>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PhysicalLocation {
+    artifact_location: ArtifactLocation,
+    region: Region,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ArtifactLocation {
+    uri: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    uri_base_id: Option<&'static str>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Region {
+    start_line: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    start_column: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    end_line: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    end_column: Option<usize>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct LogicalLocation<'a> {
+    fully_qualified_name: &'a str,
+    kind: &'static str,
+}
+
+#[derive(Serialize)]
+struct Properties {
+    metric: &'static str,
+    value: f64,
+    limit: f64,
+}
+
+/// Renders `violations` as a SARIF log. `sources` holds the source code of
+/// the files, by path, which is needed to count columns in UTF-16 code
+/// units; results in files that are missing from it have no columns.
+pub fn log(violations: &[Violation], sources: &HashMap<String, String>) -> String {
+    let rules = RULES
+        .iter()
+        .zip(HELP)
+        .map(|(&(_, id, name, description), help)| Rule {
+            id,
+            name,
+            short_description: Message {
+                text: description.to_string(),
+            },
+            help: Message {
+                text: help.to_string(),
+            },
+            default_configuration: Configuration { level: "warning" },
+        })
+        .collect();
+    let log = Log {
+        schema: SCHEMA,
+        version: VERSION,
+        runs: [Run {
+            tool: Tool {
+                driver: Driver {
+                    name: "plexity",
+                    version: env!("CARGO_PKG_VERSION"),
+                    rules,
+                },
+            },
+            column_kind: "utf16CodeUnits",
+            results: violations
+                .iter()
+                .filter_map(|violation| {
+                    result(violation, sources.get(&violation.path).map(String::as_str))
+                })
+                .collect(),
+        }],
+    };
+    serde_json::to_string_pretty(&log).unwrap()
+}
+
+fn result<'a>(violation: &'a Violation, source: Option<&str>) -> Option<SarifResult<'a>> {
+    let rule_index = RULES
+        .iter()
+        .position(|(metric, ..)| *metric == violation.metric)?;
+    let (_, rule_id, _, _) = RULES[rule_index];
+    let subject = match &violation.function {
+        Some(function) => format!("{function} has a"),
+        None => "The file has a".to_string(),
+    };
+    let message = format!(
+        "{subject} {} of {}, over the limit of {}.",
+        violation.metric.replace('_', " "),
+        number(violation.value),
+        number(violation.limit)
+    );
+    let logical_locations = violation
+        .function
+        .as_deref()
+        .map(|name| LogicalLocation {
+            fully_qualified_name: name,
+            kind: "function",
+        })
+        .into_iter()
+        .collect();
+
+    Some(SarifResult {
+        rule_id,
+        rule_index,
+        level: if violation.value >= 2.0 * violation.limit {
+            "error"
+        } else {
+            "warning"
+        },
+        message: Message { text: message },
+        locations: [Location {
+            physical_location: PhysicalLocation {
+                artifact_location: artifact_location(&violation.path),
+                region: region(violation, source),
+            },
+            logical_locations,
+        }],
+        properties: Properties {
+            metric: violation.metric,
+            value: violation.value,
+            limit: violation.limit,
+        },
+    })
+}
+
+fn region(violation: &Violation, source: Option<&str>) -> Region {
+    let column = |position: Position| utf16_column(source, position);
+    match violation.span {
+        // SARIF regions start where the violation is reported, which for
+        // depth limits is the deepest node rather than the function
+        Some(Span { start, end }) if start == violation.location => Region {
+            start_line: start.line,
+            start_column: column(start),
+            end_line: Some(end.line),
+            end_column: column(end),
+        },
+        _ => Region {
+            start_line: violation.location.line,
+            start_column: column(violation.location),
+            end_line: None,
+            end_column: None,
+        },
+    }
+}
+
+// Converts the one-based byte column of `position` into UTF-16 code units,
+// or none when the line cannot be found
+fn utf16_column(source: Option<&str>, position: Position) -> Option<usize> {
+    source
+        .and_then(|source| source.split('\n').nth(position.line - 1))
+        .and_then(|line| line.get(..position.column - 1))
+        .map(|before| before.encode_utf16().count() + 1)
+}
+
+// Relative paths are resolved against the root of the scanned sources, and
+// absolute ones become `file` URIs
+fn artifact_location(path: &str) -> ArtifactLocation {
+    let mut path = path.replace('\\', "/");
+    while let Some(rest) = path.strip_prefix("./") {
+        path = rest.to_string();
+    }
+    if path.starts_with('/') {
+        ArtifactLocation {
+            uri: format!("file://{}", encode(&path)),
+            uri_base_id: None,
+        }
+    } else {
+        ArtifactLocation {
+            uri: encode(&path),
+            uri_base_id: Some("%SRCROOT%"),
+        }
+    }
+}
+
+// Percent-encodes everything but the unreserved characters and slashes
+fn encode(path: &str) -> String {
+    let mut encoded = String::new();
+    for byte in path.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~/".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::check::{check, Limits, MetricLimits};
+    use crate::language::Language;
+    use crate::project::file_report;
+    use serde_json::Value;
+
+    const SCHEMA_DOCUMENT: &str = include_str!("../../schemas/sarif-schema-2.1.0.json");
+
+    const SOURCE: &str = "def f(a):\n    if a:\n        if a > 1:\n            return [[a]]\n";
+
+    fn violations_in(source: &str) -> Vec<Violation> {
+        let file = file_report("./src/my file.py", source, Language::Python);
+        let limits = Limits {
+            file: MetricLimits {
+                average_depth: Some(1.0),
+                ..Default::default()
+            },
+            function: MetricLimits {
+                maximum_depth: Some(3),
+                cyclomatic_complexity: Some(2),
+                ..Default::default()
+            },
+        };
+        check(&file, &limits)
+    }
+
+    fn violations() -> Vec<Violation> {
+        violations_in(SOURCE)
+    }
+
+    fn sources() -> HashMap<String, String> {
+        HashMap::from([("./src/my file.py".to_string(), SOURCE.to_string())])
+    }
+
+    #[test]
+    fn test_log_matches_the_schema() {
+        let schema: Value = serde_json::from_str(SCHEMA_DOCUMENT).unwrap();
+        let validator = jsonschema::validator_for(&schema).unwrap();
+        for violations in [violations(), Vec::new()] {
+            let value: Value = serde_json::from_str(&log(&violations, &sources())).unwrap();
+            let errors: Vec<String> = validator
+                .iter_errors(&value)
+                .map(|err| format!("{err} at {}", err.instance_path))
+                .collect();
+            assert!(errors.is_empty(), "{errors:#?}");
+        }
+    }
+
+    #[test]
+    fn test_results() {
+        let value: Value = serde_json::from_str(&log(&violations(), &sources())).unwrap();
+        let run = &value["runs"][0];
+        assert_eq!(value["version"], VERSION);
+        assert_eq!(run["columnKind"], "utf16CodeUnits");
+        assert_eq!(run["tool"]["driver"]["rules"].as_array().unwrap().len(), 4);
+
+        let results = run["results"].as_array().unwrap();
+        let rules: Vec<&str> = results
+            .iter()
+            .map(|result| result["ruleId"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            rules,
+            ["average-depth", "maximum-depth", "cyclomatic-complexity"]
+        );
+
+        // The whole file is only located by its first line
+        let file = &results[0]["locations"][0];
+        assert_eq!(
+            file["physicalLocation"]["artifactLocation"]["uri"],
+            "src/my%20file.py"
+        );
+        assert_eq!(file["physicalLocation"]["region"]["startLine"], 1);
+        assert!(file.get("logicalLocations").is_none());
+
+        // The depth limit points at the deepest node within the function
+        let depth = &results[1];
+        let region = &depth["locations"][0]["physicalLocation"]["region"];
+        assert_eq!(region["startLine"], 4);
+        assert!(region["endColumn"].is_number());
+        assert_eq!(
+            depth["locations"][0]["logicalLocations"][0]["fullyQualifiedName"],
+            "f"
+        );
+        assert_eq!(results[2]["level"], "warning");
+        assert_eq!(results[2]["ruleIndex"], 2);
+    }
+
+    #[test]
+    fn test_levels_and_uris() {
+        let mut violation = violations().remove(1);
+        violation.value = 2.0 * violation.limit;
+        assert_eq!(result(&violation, None).unwrap().level, "error");
+        assert_eq!(artifact_location("/tmp/a b.py").uri, "file:///tmp/a%20b.py");
+    }
+
+    #[test]
+    fn test_columns_count_utf16_code_units() {
+        // "é" is two bytes but one code unit, and "😀" four bytes but two
+        let source = SOURCE.replace("return [[a]]", "return \"é😀\", [[a]]");
+        let depth = violations_in(&source).remove(1);
+        let bytes = depth.span.unwrap();
+        let sources = HashMap::from([(depth.path.clone(), source)]);
+        let value: Value =
+            serde_json::from_str(&log(std::slice::from_ref(&depth), &sources)).unwrap();
+        let found = &value["runs"][0]["results"][0]["locations"][0]["physicalLocation"]["region"];
+        assert_eq!(found["startLine"], 4);
+        assert_eq!(found["startColumn"], bytes.start.column - 3);
+        assert_eq!(found["endColumn"], bytes.end.column - 3);
+
+        // Without the source, only the lines are given
+        let value: Value =
+            serde_json::from_str(&log(std::slice::from_ref(&depth), &HashMap::new())).unwrap();
+        let found = &value["runs"][0]["results"][0]["locations"][0]["physicalLocation"]["region"];
+        assert_eq!(found["startLine"], 4);
+        assert!(found.get("startColumn").is_none());
+        assert!(found.get("endColumn").is_none());
+    }
+}
@@ -0,0 +1,736 @@
+/*  A module for rendering a report as the human-readable plexity scorecard.  */
+
+use super::number;
+use crate::check::Violation;
+use crate::diff::FileDelta;
+use crate::hotspots::Hotspot;
+use crate::language::Language;
+use crate::project::{FileReport, Summary};
+use crate::report::{FunctionReport, Metrics, Report};
+use crate::syntax::TreeNode;
+use crate::{cognitive, cyclomatic, functions, halstead};
+use std::cmp::Reverse;
+use std::collections::HashSet;
+use std::fmt::Write;
+
+// The ANSI foreground colours that kinds cycle through as they get deeper:
+// cyan, green, yellow, magenta, blue and red
+const DEPTH_COLOURS: [u8; 6] = [36, 32, 33, 35, 34, 31];
+
+/// Renders `report` as the "PLEXITY SCORECARD" block, followed by a
+/// breakdown of each function when any were found.
+pub fn scorecard(report: &Report) -> String {
+    let metrics = &report.metrics;
+    let mut out = String::new();
+    writeln!(out, "============ PLEXITY SCORECARD ============\n").unwrap();
+    writeln!(
+        out,
+        "  - Number of nodes found in tree: {}",
+        metrics.node_count
+    )
+    .unwrap();
+    if metrics.suppressed_nodes > 0 {
+        writeln!(
+            out,
+            "  - Nodes suppressed by comments: {}",
+            metrics.suppressed_nodes
+        )
+        .unwrap();
+    }
+    writeln!(
+        out,
+        "  - Maximum depth of syntax tree: {}",
+        metrics.maximum_depth
+    )
+    .unwrap();
+    writeln!(
+        out,
+        "  - Combined depths of all nodes: {}",
+        metrics.combined_depth
+    )
+    .unwrap();
+    writeln!(
+        out,
+        "  - Average depth across syntax tree: {:.2}",
+        metrics.average_depth
+    )
+    .unwrap();
+    if let Some(cyclomatic_complexity) = metrics.cyclomatic_complexity {
+        writeln!(out, "  - Cyclomatic complexity: {}", cyclomatic_complexity).unwrap();
+    }
+    if let Some(cognitive_complexity) = metrics.cognitive_complexity {
+        writeln!(out, "  - Cognitive complexity: {}", cognitive_complexity).unwrap();
+    }
+    if let Some(halstead) = &metrics.halstead {
+        writeln!(out, "  - Halstead vocabulary: {}", halstead.vocabulary).unwrap();
+        writeln!(out, "  - Halstead length: {}", halstead.length).unwrap();
+        writeln!(out, "  - Halstead volume: {:.2}", halstead.volume).unwrap();
+        writeln!(out, "  - Halstead difficulty: {:.2}", halstead.difficulty).unwrap();
+        writeln!(out, "  - Halstead effort: {:.2}", halstead.effort).unwrap();
+        writeln!(
+            out,
+            "  - Halstead estimated bugs: {:.3}",
+            halstead.estimated_bugs
+        )
+        .unwrap();
+    }
+    writeln!(out, "  - Source lines of code: {}", metrics.source_lines).unwrap();
+    if let Some(maintainability) = &metrics.maintainability {
+        writeln!(
+            out,
+            "  - Maintainability index: {:.2} (normalized: {:.2})",
+            maintainability.classic, maintainability.normalized
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "  - Maintainability index using average depth: {:.2} (normalized: {:.2})",
+            maintainability.depth_classic, maintainability.depth_normalized
+        )
+        .unwrap();
+    }
+
+    if !report.functions.is_empty() {
+        writeln!(out, "\n============ FUNCTION BREAKDOWN ============\n").unwrap();
+        for function in &report.functions {
+            writeln!(out, "{}", function_line(function)).unwrap();
+        }
+    }
+    out
+}
+
+/// Renders the "PROJECT SUMMARY" block for several analysed files.
+pub fn summary(summary: &Summary) -> String {
+    let mut out = String::new();
+    writeln!(out, "============ PROJECT SUMMARY ============\n").unwrap();
+    writeln!(out, "  - Files analysed: {}", summary.file_count).unwrap();
+    writeln!(out, "  - Functions found: {}", summary.function_count).unwrap();
+    writeln!(out, "  - Total number of nodes: {}", summary.node_count).unwrap();
+    writeln!(
+        out,
+        "  - Total source lines of code: {}",
+        summary.source_lines
+    )
+    .unwrap();
+    writeln!(
+        out,
+        "  - Maximum depth across all files: {}",
+        summary.maximum_depth
+    )
+    .unwrap();
+    writeln!(
+        out,
+        "  - Average depth across all files: {:.2}",
+        summary.average_depth
+    )
+    .unwrap();
+    if let Some(mean) = summary.mean_cyclomatic_complexity {
+        writeln!(out, "  - Mean cyclomatic complexity: {:.2}", mean).unwrap();
+    }
+    if let Some(mean) = summary.mean_cognitive_complexity {
+        writeln!(out, "  - Mean cognitive complexity: {:.2}", mean).unwrap();
+    }
+    if let Some(mean) = summary.mean_maintainability {
+        writeln!(
+            out,
+            "  - Mean maintainability index (normalized): {:.2}",
+            mean
+        )
+        .unwrap();
+    }
+    if !summary.worst_files.is_empty() {
+        writeln!(out, "\n  Most deeply nested files:").unwrap();
+        for file in &summary.worst_files {
+            writeln!(
+                out,
+                "  - {}: maximum depth {}, average depth {:.2}",
+                file.path, file.maximum_depth, file.average_depth
+            )
+            .unwrap();
+        }
+    }
+    out
+}
+
+/// Renders the metrics of two files side by side, along with the change
+/// from the first to the second.
+pub fn comparison(before: &FileReport, after: &FileReport) -> String {
+    let rows = comparison_rows(&before.report.metrics, &after.report.metrics);
+    let label_width = rows.iter().map(|row| row.0.len()).max().unwrap_or(0);
+    let before_width = before.path.len().max(8);
+    let after_width = after.path.len().max(8);
+
+    let mut out = String::new();
+    writeln!(out, "============ PLEXITY COMPARISON ============\n").unwrap();
+    writeln!(
+        out,
+        "  {:label_width$}  {:>before_width$}  {:>after_width$}  {:>8}",
+        "", before.path, after.path, "Change"
+    )
+    .unwrap();
+    for (label, old, new, precision) in rows {
+        let change = match (old, new) {
+            (Some(old), Some(new)) => format!("{:+.precision$}", new - old),
+            _ => "-".to_string(),
+        };
+        writeln!(
+            out,
+            "  {:label_width$}  {:>before_width$}  {:>after_width$}  {:>8}",
+            label,
+            optional(old, precision),
+            optional(new, precision),
+            change
+        )
+        .unwrap();
+    }
+    out
+}
+
+// The label, both values and the number of decimal places for each metric
+type ComparisonRow = (&'static str, Option<f64>, Option<f64>, usize);
+
+fn comparison_rows(before: &Metrics, after: &Metrics) -> Vec<ComparisonRow> {
+    let count = |value: usize| Some(value as f64);
+    let optional_count = |value: Option<usize>| value.map(|value| value as f64);
+    vec![
+        (
+            "Number of nodes",
+            count(before.node_count),
+            count(after.node_count),
+            0,
+        ),
+        (
+            "Maximum depth",
+            count(before.maximum_depth),
+            count(after.maximum_depth),
+            0,
+        ),
+        (
+            "Combined depth",
+            count(before.combined_depth),
+            count(after.combined_depth),
+            0,
+        ),
+        (
+            "Average depth",
+            Some(before.average_depth),
+            Some(after.average_depth),
+            2,
+        ),
+        (
+            "Cyclomatic complexity",
+            optional_count(before.cyclomatic_complexity),
+            optional_count(after.cyclomatic_complexity),
+            0,
+        ),
+        (
+            "Cognitive complexity",
+            optional_count(before.cognitive_complexity),
+            optional_count(after.cognitive_complexity),
+            0,
+        ),
+        (
+            "Halstead volume",
+            before.halstead.as_ref().map(|halstead| halstead.volume),
+            after.halstead.as_ref().map(|halstead| halstead.volume),
+            2,
+        ),
+        (
+            "Source lines of code",
+            count(before.source_lines),
+            count(after.source_lines),
+            0,
+        ),
+        (
+            "Maintainability (normalized)",
+            before
+                .maintainability
+                .as_ref()
+                .map(|index| index.normalized),
+            after.maintainability.as_ref().map(|index| index.normalized),
+            2,
+        ),
+    ]
+}
+
+fn optional(value: Option<f64>, precision: usize) -> String {
+    match value {
+        Some(value) => format!("{value:.precision$}"),
+        None => "-".to_string(),
+    }
+}
+
+/// Renders the deepest `count` hotspots across `files`, deepest first.
+pub fn hotspots(files: &[FileReport], count: usize) -> String {
+    let mut ranked: Vec<(&str, &Hotspot)> = files
+        .iter()
+        .flat_map(|file| {
+            let hotspots = file.hotspots.iter().flatten();
+            hotspots.map(move |hotspot| (file.path.as_str(), hotspot))
+        })
+        .collect();
+    // The sort is stable, so ties keep the order of the files
+    ranked.sort_by_key(|(_, hotspot)| Reverse(hotspot.depth));
+
+    let mut out = String::new();
+    writeln!(out, "============ NESTING HOTSPOTS ============\n").unwrap();
+    if ranked.is_empty() {
+        writeln!(out, "  No hotspots were found.").unwrap();
+    }
+    for (path, hotspot) in ranked.into_iter().take(count) {
+        let span = &hotspot.span;
+        writeln!(
+            out,
+            "  - {}:{}:{}-{}:{} ({}, depth {})",
+            path,
+            span.start.line,
+            span.start.column,
+            span.end.line,
+            span.end.column,
+            hotspot.kind,
+            hotspot.depth
+        )
+        .unwrap();
+        for line in hotspot.snippet.lines() {
+            writeln!(out, "        {}", line).unwrap();
+        }
+    }
+    out
+}
+
+/// Renders a table of the supported languages, their file extensions and
+/// the metrics that can be calculated for each.
+pub fn languages() -> String {
+    let extensions: Vec<String> = Language::ALL.into_iter().map(extension_list).collect();
+    let width = extensions.iter().map(String::len).max().unwrap_or(0) + 2;
+
+    let mut out = String::new();
+    writeln!(out, "  {:15}{:width$}Metrics", "Language", "Extensions").unwrap();
+    for (language, extensions) in Language::ALL.into_iter().zip(extensions) {
+        writeln!(
+            out,
+            "  {:15}{:width$}{}",
+            language.name(),
+            extensions,
+            supported_metrics(language).join(", ")
+        )
+        .unwrap();
+    }
+    out
+}
+
+fn extension_list(language: Language) -> String {
+    let mut names: Vec<String> = language
+        .extensions()
+        .iter()
+        .map(|extension| format!(".{extension}"))
+        .collect();
+    match language {
+        Language::Dockerfile => names.insert(0, "Dockerfile".to_string()),
+        Language::Gitattributes => names.push(".gitattributes".to_string()),
+        _ => {}
+    }
+    names.join(" ")
+}
+
+fn supported_metrics(language: Language) -> Vec<&'static str> {
+    let mut metrics = vec!["depth"];
+    if cyclomatic::supported(language) {
+        metrics.push("cyclomatic");
+    }
+    if cognitive::supported(language) {
+        metrics.push("cognitive");
+    }
+    if halstead::supported(language) {
+        metrics.push("halstead");
+    }
+    if halstead::supported(language) && cyclomatic::supported(language) {
+        metrics.push("maintainability");
+    }
+    if !functions::function_kinds(language).is_empty() {
+        metrics.push("functions");
+    }
+    metrics
+}
+
+/// Renders the limits that were exceeded, one line per violation.
+pub fn violations(violations: &[Violation]) -> String {
+    let mut out = String::new();
+    for violation in violations {
+        let subject = match &violation.function {
+            Some(function) => format!(" of {function}"),
+            None => String::new(),
+        };
+        writeln!(
+            out,
+            "  - {}:{}:{}: {}{} is {}, over the limit of {}",
+            violation.path,
+            violation.location.line,
+            violation.location.column,
+            violation.metric.replace('_', " "),
+            subject,
+            number(violation.value),
+            number(violation.limit)
+        )
+        .unwrap();
+    }
+    out
+}
+
+/// Renders the "PLEXITY DIFF" block: how the metrics of each changed file
+/// moved, followed by the functions that were added or modified.
+pub fn diff(files: &[FileDelta]) -> String {
+    let mut out = String::new();
+    writeln!(out, "============ PLEXITY DIFF ============").unwrap();
+    if files.is_empty() {
+        writeln!(out, "\nNo source files were changed.").unwrap();
+    }
+    for file in files {
+        match &file.old_path {
+            Some(old_path) => writeln!(out, "\n{} (renamed from {old_path})", file.path),
+            None => writeln!(out, "\n{} ({})", file.path, file.status.name()),
+        }
+        .unwrap();
+        for (label, before, after, precision) in delta_rows(file.before.as_ref(), &file.after) {
+            writeln!(out, "  - {label}: {}", change(before, after, precision)).unwrap();
+        }
+        if file.functions.is_empty() {
+            writeln!(out, "  No functions were added or modified.").unwrap();
+            continue;
+        }
+        writeln!(out, "\n  Functions added or modified:").unwrap();
+        for function in &file.functions {
+            let changes: Vec<String> = delta_rows(function.before.as_ref(), &function.after)
+                .into_iter()
+                .map(|(label, before, after, precision)| {
+                    format!(
+                        "{} {}",
+                        label.to_lowercase(),
+                        change(before, after, precision)
+                    )
+                })
+                .collect();
+            writeln!(
+                out,
+                "  - {} ({}, lines {}-{}): {}",
+                function.name,
+                function.status.name(),
+                function.span.start.line,
+                function.span.end.line,
+                changes.join(", ")
+            )
+            .unwrap();
+        }
+    }
+    out
+}
+
+/// Renders the tree made up of `nodes` with box-drawing lines, one node
+/// per line, under a `root` line such as the path of the file. Each line
+/// holds the field the node fills, its kind (quoted when it is anonymous),
+/// its span and depth, and the source text of named leaves. With `colour`,
+/// kinds are coloured by their depth using ANSI escape codes.
+pub fn tree(root: &str, nodes: &[TreeNode], colour: bool) -> String {
+    // Whether each node is the last child of its parent, found by seeing
+    // which node comes last for each parent
+    let mut last = vec![false; nodes.len()];
+    let mut parents_seen = HashSet::new();
+    for (i, node) in nodes.iter().enumerate().rev() {
+        last[i] = parents_seen.insert(node.parent);
+    }
+
+    let mut out = String::new();
+    writeln!(out, "{root}").unwrap();
+    for (i, node) in nodes.iter().enumerate() {
+        let mut ancestors = Vec::new();
+        let mut ancestor = node.parent;
+        while let Some(a) = ancestor {
+            ancestors.push(if last[a] { "    " } else { "│   " });
+            ancestor = nodes[a].parent;
+        }
+        for guide in ancestors.iter().rev() {
+            out.push_str(guide);
+        }
+        out.push_str(if last[i] { "└── " } else { "├── " });
+
+        if let Some(field) = node.field {
+            write!(out, "{field}: ").unwrap();
+        }
+        let kind = if node.named {
+            node.kind.clone()
+        } else {
+            format!("{:?}", node.kind)
+        };
+        if colour {
+            let code = DEPTH_COLOURS[node.depth % DEPTH_COLOURS.len()];
+            write!(out, "\x1b[{code}m{kind}\x1b[0m").unwrap();
+        } else {
+            out.push_str(&kind);
+        }
+        let span = &node.span;
+        write!(
+            out,
+            " {}:{}-{}:{} depth {}",
+            span.start.line, span.start.column, span.end.line, span.end.column, node.depth
+        )
+        .unwrap();
+        if let Some(text) = node.text.as_ref().filter(|_| node.named) {
+            write!(out, " {text:?}").unwrap();
+        }
+        writeln!(out).unwrap();
+    }
+    out
+}
+
+// The metrics shown in a diff, leaving out those the language lacks
+fn delta_rows(before: Option<&Metrics>, after: &Metrics) -> Vec<ComparisonRow> {
+    const SHOWN: [&str; 4] = [
+        "Maximum depth",
+        "Average depth",
+        "Cyclomatic complexity",
+        "Cognitive complexity",
+    ];
+    comparison_rows(before.unwrap_or(after), after)
+        .into_iter()
+        .filter(|(label, _, after, _)| SHOWN.contains(label) && after.is_some())
+        .map(|(label, old, new, precision)| {
+            (label, old.filter(|_| before.is_some()), new, precision)
+        })
+        .collect()
+}
+
+// Renders a value that moved as `6 -> 8 (+2)`, or just the new value
+fn change(before: Option<f64>, after: Option<f64>, precision: usize) -> String {
+    match (before, after) {
+        (Some(before), Some(after)) => format!(
+            "{before:.precision$} -> {after:.precision$} ({:+.precision$})",
+            after - before
+        ),
+        _ => optional(after, precision),
+    }
+}
+
+fn function_line(function: &FunctionReport) -> String {
+    let metrics = &function.metrics;
+    let mut line = format!(
+        "  - {} (lines {}-{}): {} nodes, maximum depth {}, average depth {:.2}",
+        function.name,
+        function.span.start.line,
+        function.span.end.line,
+        metrics.node_count,
+        metrics.maximum_depth,
+        metrics.average_depth
+    );
+    if let Some(cyclomatic_complexity) = metrics.cyclomatic_complexity {
+        write!(line, ", cyclomatic complexity {}", cyclomatic_complexity).unwrap();
+    }
+    if let Some(cognitive_complexity) = metrics.cognitive_complexity {
+        write!(line, ", cognitive complexity {}", cognitive_complexity).unwrap();
+    }
+    if let Some(halstead) = &metrics.halstead {
+        write!(line, ", Halstead volume {:.2}", halstead.volume).unwrap();
+    }
+    if let Some(maintainability) = &metrics.maintainability {
+        write!(line, ", maintainability {:.2}", maintainability.normalized).unwrap();
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::file_report;
+    use crate::report::{Metrics, Position};
+
+    #[test]
+    fn test_scorecard_omits_missing_cyclomatic() {
+        let report = Report {
+            metrics: Metrics {
+                node_count: 4,
+                suppressed_nodes: 0,
+                maximum_depth: 2,
+                deepest_node: None,
+                combined_depth: 5,
+                average_depth: 1.25,
+                cyclomatic_complexity: None,
+                cognitive_complexity: None,
+                halstead: None,
+                source_lines: 3,
+                maintainability: None,
+            },
+            functions: Vec::new(),
+        };
+        let rendered = scorecard(&report);
+        assert!(rendered.contains("Average depth across syntax tree: 1.25"));
+        assert!(!rendered.contains("Cyclomatic"));
+        assert!(!rendered.contains("Halstead"));
+        assert!(rendered.contains("Source lines of code: 3"));
+        assert!(!rendered.contains("Maintainability"));
+        assert!(!rendered.contains("FUNCTION BREAKDOWN"));
+        assert!(!rendered.contains("suppressed"));
+    }
+
+    #[test]
+    fn test_summary_lists_worst_files() {
+        let summary = Summary {
+            file_count: 2,
+            worst_files: vec![crate::project::WorstFile {
+                path: "deep.py".to_string(),
+                maximum_depth: 9,
+                average_depth: 4.5,
+                cyclomatic_complexity: Some(3),
+            }],
+            ..Default::default()
+        };
+        let rendered = super::summary(&summary);
+        assert!(rendered.contains("Files analysed: 2"));
+        assert!(rendered.contains("  - deep.py: maximum depth 9, average depth 4.50"));
+        assert!(!rendered.contains("Mean cyclomatic"));
+    }
+
+    #[test]
+    fn test_scorecard_lists_functions() {
+        let report =
+            crate::analyze("def greet():\n    print(1)\n", crate::Language::Python).unwrap();
+        let rendered = scorecard(&report);
+        assert!(rendered.contains("  - greet (lines 1-2): "));
+        assert!(rendered.contains("cyclomatic complexity 1"));
+        assert!(rendered.contains("cognitive complexity 0"));
+    }
+
+    #[test]
+    fn test_comparison_shows_changes() {
+        let rendered = comparison(
+            &file_report("old.py", "x = 1\n", Language::Python),
+            &file_report("new.py", "if x:\n    x = 1\n", Language::Python),
+        );
+        assert!(rendered.contains("old.py"));
+        let row = rendered
+            .lines()
+            .find(|line| line.contains("Cyclomatic complexity"))
+            .unwrap();
+        assert!(row.ends_with("1         2        +1"));
+    }
+
+    #[test]
+    fn test_languages_lists_metrics() {
+        let rendered = languages();
+        assert!(rendered.contains("  python         .py .pyi "));
+        let json = rendered
+            .lines()
+            .find(|line| line.contains(".json"))
+            .unwrap();
+        assert!(json.ends_with("depth"));
+    }
+
+    #[test]
+    fn test_diff_shows_function_changes() {
+        let before = "def f(a):\n    return a\n";
+        let after = "def f(a):\n    if a:\n        return a\n\ndef g():\n    pass\n";
+        let before_report = crate::analyze(before, Language::Python).unwrap();
+        let after_report = crate::analyze(after, Language::Python).unwrap();
+        let file = FileDelta {
+            path: "a.py".to_string(),
+            old_path: None,
+            status: crate::git::Status::Modified,
+            language: Language::Python,
+            functions: crate::diff::function_deltas(
+                Some((before, &before_report)),
+                (after, &after_report),
+            ),
+            before: Some(before_report.metrics),
+            after: after_report.metrics,
+        };
+        let rendered = diff(&[file]);
+        assert!(rendered.contains("a.py (modified)"));
+        assert!(rendered.contains("  - Cyclomatic complexity: 1 -> 2 (+1)"));
+        assert!(rendered.contains("  - f (modified, lines 1-3): maximum depth"));
+        assert!(rendered.contains("  - g (added, lines 5-6): maximum depth"));
+        assert!(diff(&[]).contains("No source files were changed."));
+    }
+
+    #[test]
+    fn test_violations_formats_numbers() {
+        let rendered = violations(&[
+            Violation {
+                path: "a.py".to_string(),
+                function: None,
+                metric: "average_depth",
+                value: 3.456,
+                limit: 3.0,
+                location: Position { line: 1, column: 1 },
+                span: None,
+            },
+            Violation {
+                path: "a.py".to_string(),
+                function: Some("Parser.parse".to_string()),
+                metric: "maximum_depth",
+                value: 9.0,
+                limit: 8.0,
+                location: Position {
+                    line: 12,
+                    column: 5,
+                },
+                span: None,
+            },
+        ]);
+        assert_eq!(
+            rendered,
+            "  - a.py:1:1: average depth is 3.46, over the limit of 3\n  - a.py:12:5: maximum depth of Parser.parse is 9, over the limit of 8\n"
+        );
+    }
+
+    #[test]
+    fn test_hotspots_ranked_across_files() {
+        let file = |path: &str, depth: usize| FileReport {
+            hotspots: Some(vec![Hotspot {
+                kind: "call".to_string(),
+                span: crate::report::Span {
+                    start: crate::report::Position { line: 3, column: 5 },
+                    end: crate::report::Position { line: 4, column: 2 },
+                },
+                depth,
+                snippet: "f(\n)".to_string(),
+            }]),
+            ..FileReport::new(path.to_string(), Language::Python, Report::default())
+        };
+        let rendered = hotspots(&[file("a.py", 4), file("b.py", 9)], 1);
+        assert!(rendered.contains("  - b.py:3:5-4:2 (call, depth 9)\n        f(\n        )\n"));
+        assert!(!rendered.contains("a.py"));
+    }
+
+    #[test]
+    fn test_tree() {
+        use crate::syntax::{Collector, Cutoff};
+        let nodes = |cutoff| {
+            let source = "if a:\n    b = 1\n";
+            let mut collector = Collector::new(cutoff);
+            crate::analyze_with(source, Language::Python, |node, record| {
+                collector.visit(node, record, source)
+            })
+            .unwrap();
+            collector.finish()
+        };
+        let named = nodes(Cutoff {
+            named_only: true,
+            ..Default::default()
+        });
+        assert_eq!(
+            tree("a.py", &named, false),
+            "a.py
+└── if_statement 1:1-2:10 depth 0
+    ├── condition: identifier 1:4-1:5 depth 1 \"a\"
+    └── consequence: block 2:5-2:10 depth 1
+        └── expression_statement 2:5-2:10 depth 2
+            └── assignment 2:5-2:10 depth 3
+                ├── left: identifier 2:5-2:6 depth 4 \"b\"
+                └── right: integer 2:9-2:10 depth 4 \"1\"
+"
+        );
+        assert!(tree("a.py", &named, true).contains("consequence: \x1b[32mblock\x1b[0m 2:5"));
+
+        let all = tree("a.py", &nodes(Cutoff::default()), false);
+        assert!(all.contains("    ├── \"if\" 1:1-1:3 depth 1\n"));
+        assert!(all.contains("    ├── \":\" 1:5-1:6 depth 1\n"));
+    }
+}
@@ -0,0 +1,183 @@
+/*  A module for collecting the reports of several files and rolling
+them up into a project-level summary.  */
+
+use crate::heatmap::Heatmap;
+use crate::hotspots::Hotspot;
+use crate::language::Language;
+use crate::report::{NodeRecord, Report};
+use serde::Serialize;
+
+/// The number of files listed in a summary's `worst_files`.
+pub const WORST_FILE_COUNT: usize = 5;
+
+/// The report for one analysed file.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FileReport {
+    #[serde(rename = "file")]
+    pub path: String,
+    pub language: Language,
+    #[serde(flatten)]
+    pub report: Report,
+    /// Every node in the file, when they were requested.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nodes: Option<Vec<NodeRecord>>,
+    /// The most deeply nested regions of the file, when they were requested.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hotspots: Option<Vec<Hotspot>>,
+    /// The source shaded by depth, for the HTML report, when it was requested.
+    #[serde(skip)]
+    pub heatmap: Option<Heatmap>,
+}
+
+impl FileReport {
+    /// The report for the file at `path`, without any of the details that
+    /// are only gathered on request.
+    pub fn new(path: String, language: Language, report: Report) -> FileReport {
+        FileReport {
+            path,
+            language,
+            report,
+            nodes: None,
+            hotspots: None,
+            heatmap: None,
+        }
+    }
+}
+
+/// Analyses `source` as the file at `path`, for use in tests.
+#[cfg(test)]
+pub(crate) fn file_report(path: &str, source: &str, language: Language) -> FileReport {
+    FileReport::new(
+        path.to_string(),
+        language,
+        crate::analyze(source, language).unwrap(),
+    )
+}
+
+/// Totals and means across every analysed file.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Summary {
+    pub file_count: usize,
+    pub function_count: usize,
+    pub node_count: usize,
+    pub source_lines: usize,
+    pub combined_depth: usize,
+    /// The deepest node found in any file.
+    pub maximum_depth: usize,
+    /// Combined depth divided by the number of nodes across all files.
+    pub average_depth: f64,
+    /// Means over the files for which each metric could be calculated.
+    pub mean_cyclomatic_complexity: Option<f64>,
+    pub mean_cognitive_complexity: Option<f64>,
+    pub mean_maintainability: Option<f64>,
+    /// The most deeply nested files, deepest first.
+    pub worst_files: Vec<WorstFile>,
+}
+
+/// A file singled out in a summary for being deeply nested.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WorstFile {
+    #[serde(rename = "file")]
+    pub path: String,
+    pub maximum_depth: usize,
+    pub average_depth: f64,
+    pub cyclomatic_complexity: Option<usize>,
+}
+
+/// Rolls the reports for several files up into a single summary.
+pub fn summarize(files: &[FileReport]) -> Summary {
+    let mut summary = Summary {
+        file_count: files.len(),
+        ..Default::default()
+    };
+    for file in files {
+        let metrics = &file.report.metrics;
+        summary.function_count += file.report.functions.len();
+        summary.node_count += metrics.node_count;
+        summary.source_lines += metrics.source_lines;
+        summary.combined_depth += metrics.combined_depth;
+        summary.maximum_depth = summary.maximum_depth.max(metrics.maximum_depth);
+    }
+    if summary.node_count > 0 {
+        summary.average_depth = summary.combined_depth as f64 / summary.node_count as f64;
+    }
+
+    summary.mean_cyclomatic_complexity = mean(files, |file| {
+        file.report
+            .metrics
+            .cyclomatic_complexity
+            .map(|value| value as f64)
+    });
+    summary.mean_cognitive_complexity = mean(files, |file| {
+        file.report
+            .metrics
+            .cognitive_complexity
+            .map(|value| value as f64)
+    });
+    summary.mean_maintainability = mean(files, |file| {
+        file.report
+            .metrics
+            .maintainability
+            .as_ref()
+            .map(|maintainability| maintainability.normalized)
+    });
+
+    let mut ranked: Vec<&FileReport> = files.iter().collect();
+    ranked.sort_by(|a, b| {
+        let (a, b) = (&a.report.metrics, &b.report.metrics);
+        b.maximum_depth
+            .cmp(&a.maximum_depth)
+            .then(b.average_depth.total_cmp(&a.average_depth))
+    });
+    summary.worst_files = ranked
+        .into_iter()
+        .take(WORST_FILE_COUNT)
+        .map(|file| WorstFile {
+            path: file.path.clone(),
+            maximum_depth: file.report.metrics.maximum_depth,
+            average_depth: file.report.metrics.average_depth,
+            cyclomatic_complexity: file.report.metrics.cyclomatic_complexity,
+        })
+        .collect();
+    summary
+}
+
+fn mean<F>(files: &[FileReport], value: F) -> Option<f64>
+where
+    F: Fn(&FileReport) -> Option<f64>,
+{
+    let values: Vec<f64> = files.iter().filter_map(value).collect();
+    if values.is_empty() {
+        return None;
+    }
+    Some(values.iter().sum::<f64>() / values.len() as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summarize_totals_and_means() {
+        let files = vec![
+            file_report("a.py", "x = 1\n", Language::Python),
+            file_report("b.py", "if x:\n    y = [1, [2]]\n", Language::Python),
+            file_report("c.json", "{}", Language::Json),
+        ];
+        let summary = summarize(&files);
+        let total: usize = files.iter().map(|f| f.report.metrics.node_count).sum();
+        assert_eq!(summary.file_count, 3);
+        assert_eq!(summary.node_count, total);
+        assert_eq!(summary.mean_cyclomatic_complexity, Some(1.5));
+        assert_eq!(summary.worst_files[0].path, "b.py");
+        assert_eq!(summary.worst_files.len(), 3);
+    }
+
+    #[test]
+    fn test_summarize_nothing() {
+        let summary = summarize(&[]);
+        assert_eq!(summary.average_depth, 0.0);
+        assert_eq!(summary.mean_cyclomatic_complexity, None);
+        assert!(summary.worst_files.is_empty());
+    }
+}
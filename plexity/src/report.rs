@@ -0,0 +1,180 @@
+/*  A module for the metrics that make up a plexity scorecard.  */
+
+use crate::halstead::Halstead;
+use crate::maintainability::Maintainability;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use tree_sitter::{Point, Range};
+
+/// The complexity metrics gathered from a single syntax tree.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Report {
+    #[serde(flatten)]
+    pub metrics: Metrics,
+    /// The metrics for each function or method found in the tree.
+    pub functions: Vec<FunctionReport>,
+}
+
+impl Report {
+    /// Clears every optional metric that is not in `enabled`, throughout
+    /// the report. Depths are always kept.
+    pub fn retain_metrics(&mut self, enabled: &[Metric]) {
+        if !enabled.contains(&Metric::Functions) {
+            self.functions.clear();
+        }
+        self.metrics.retain(enabled);
+        for function in &mut self.functions {
+            function.metrics.retain(enabled);
+        }
+    }
+}
+
+/// The metrics that can be turned off, leaving just the depths.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Metric {
+    Cyclomatic,
+    Cognitive,
+    Halstead,
+    Maintainability,
+    Functions,
+}
+
+impl Metric {
+    pub const ALL: [Metric; 5] = [
+        Metric::Cyclomatic,
+        Metric::Cognitive,
+        Metric::Halstead,
+        Metric::Maintainability,
+        Metric::Functions,
+    ];
+
+    /// The name used for the metric in configuration and on the command line.
+    pub fn name(self) -> &'static str {
+        match self {
+            Metric::Cyclomatic => "cyclomatic",
+            Metric::Cognitive => "cognitive",
+            Metric::Halstead => "halstead",
+            Metric::Maintainability => "maintainability",
+            Metric::Functions => "functions",
+        }
+    }
+}
+
+impl FromStr for Metric {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Metric, String> {
+        Metric::ALL
+            .into_iter()
+            .find(|metric| metric.name() == name.trim())
+            .ok_or_else(|| {
+                let names: Vec<&str> = Metric::ALL.iter().map(|m| m.name()).collect();
+                format!(
+                    "\"{name}\" is not a metric; the metrics are {}",
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+/// The metrics gathered from a syntax tree or one of its subtrees.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Metrics {
+    /// Number of nodes found below the measured node.
+    pub node_count: usize,
+    /// Number of nodes left out because of suppression comments.
+    pub suppressed_nodes: usize,
+    /// Depth of the most deeply nested node.
+    pub maximum_depth: usize,
+    /// Where the first of the most deeply nested nodes is found.
+    pub deepest_node: Option<Span>,
+    /// Sum of the depths of every node.
+    pub combined_depth: usize,
+    /// Combined depth divided by the number of nodes.
+    pub average_depth: f64,
+    /// Cyclomatic complexity, for languages where it can be calculated.
+    pub cyclomatic_complexity: Option<usize>,
+    /// Cognitive complexity, for languages where it can be calculated.
+    pub cognitive_complexity: Option<usize>,
+    /// Halstead's metrics, for languages where they can be calculated.
+    pub halstead: Option<Halstead>,
+    /// Number of lines holding code rather than whitespace or comments.
+    pub source_lines: usize,
+    /// Maintainability Index, where Halstead's metrics and the cyclomatic
+    /// complexity are both available.
+    pub maintainability: Option<Maintainability>,
+}
+
+impl Metrics {
+    fn retain(&mut self, enabled: &[Metric]) {
+        if !enabled.contains(&Metric::Cyclomatic) {
+            self.cyclomatic_complexity = None;
+        }
+        if !enabled.contains(&Metric::Cognitive) {
+            self.cognitive_complexity = None;
+        }
+        if !enabled.contains(&Metric::Halstead) {
+            self.halstead = None;
+        }
+        if !enabled.contains(&Metric::Maintainability) {
+            self.maintainability = None;
+        }
+    }
+}
+
+/// The metrics for a single function or method.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FunctionReport {
+    /// The function's name, qualified by any enclosing classes or functions.
+    pub name: String,
+    pub span: Span,
+    /// Metrics for the function's body, with depths counted from the
+    /// function itself rather than from the root of the file.
+    #[serde(flatten)]
+    pub metrics: Metrics,
+}
+
+/// A line and column in a source file, both counted from one.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl From<Point> for Position {
+    fn from(point: Point) -> Position {
+        Position {
+            line: point.row + 1,
+            column: point.column + 1,
+        }
+    }
+}
+
+/// The region of a source file covered by a syntax tree node.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct Span {
+    pub start: Position,
+    pub end: Position,
+}
+
+impl From<Range> for Span {
+    fn from(range: Range) -> Span {
+        Span {
+            start: range.start_point.into(),
+            end: range.end_point.into(),
+        }
+    }
+}
+
+/// The details recorded for each node visited while traversing a tree.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NodeRecord {
+    /// Position of the node in the traversal order, counted from one.
+    pub index: usize,
+    pub depth: usize,
+    pub kind: String,
+    pub span: Span,
+    /// Whether the node counts towards the cyclomatic complexity.
+    pub cyclomatic: bool,
+}
@@ -0,0 +1,211 @@
+/*  A module for finding the code that inline comments exclude from the
+metrics. A comment holding one of these directives
+
+    plexity: ignore-next     the construct that follows the comment
+    plexity: ignore-start    everything up to the next `ignore-end`
+    plexity: ignore-end
+    plexity: ignore-file     the whole file
+
+suppresses the subtrees it covers, which are then skipped while measuring
+depths and complexity. Comments are recognised by each grammar's own node
+kinds, so a directive inside a string is never mistaken for one.  */
+
+use crate::language::Language;
+use std::ops::Range;
+use tree_sitter::Node;
+
+// The marker that starts every directive
+const MARKER: &str = "plexity:";
+
+// The node kinds of comments in each language
+fn comment_kinds(language: Language) -> &'static [&'static str] {
+    match language {
+        Language::C
+        | Language::Cpp
+        | Language::Dockerfile
+        | Language::Gitattributes
+        | Language::Html
+        | Language::JavaScript
+        | Language::Php
+        | Language::Python
+        | Language::Toml => &["comment"],
+        Language::Css => &["comment", "js_comment"],
+        Language::Java => &["comment", "line_comment", "block_comment"],
+        Language::Rust => &["line_comment", "block_comment"],
+        Language::TypeScript | Language::Tsx => &["comment", "html_comment"],
+        Language::Json | Language::Markdown => &[],
+    }
+}
+
+/// Whether suppression comments can be written in `language`.
+pub fn supported(language: Language) -> bool {
+    !comment_kinds(language).is_empty()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Directive {
+    Next,
+    Start,
+    End,
+    File,
+}
+
+/// The regions of a file that suppression comments exclude.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Suppressions {
+    file: bool,
+    regions: Vec<Range<usize>>,
+}
+
+impl Suppressions {
+    /// Reads the suppression comments in the tree below `root`.
+    pub fn find(root: Node, source: &str, language: Language) -> Suppressions {
+        let mut comments = Vec::new();
+        collect_comments(root, comment_kinds(language), &mut comments);
+
+        let mut suppressions = Suppressions::default();
+        let mut open: Option<usize> = None;
+        for comment in comments {
+            let text = comment.utf8_text(source.as_bytes()).unwrap_or_default();
+            match directive(text) {
+                Some(Directive::File) => suppressions.file = true,
+                Some(Directive::Next) => {
+                    if let Some(next) = next_construct(root, comment, source, language) {
+                        suppressions.regions.push(next.byte_range());
+                    }
+                }
+                Some(Directive::Start) => {
+                    open = open.or(Some(comment.start_byte()));
+                }
+                Some(Directive::End) => {
+                    if let Some(start) = open.take() {
+                        suppressions.regions.push(start..comment.end_byte());
+                    }
+                }
+                None => {}
+            }
+        }
+        // A region that is never closed runs to the end of the file
+        if let Some(start) = open {
+            suppressions.regions.push(start..source.len());
+        }
+        suppressions
+    }
+
+    /// Whether `node` is suppressed in full.
+    pub fn covers(&self, node: Node) -> bool {
+        self.file
+            || self
+                .regions
+                .iter()
+                .any(|region| region.start <= node.start_byte() && node.end_byte() <= region.end)
+    }
+}
+
+fn collect_comments<'tree>(node: Node<'tree>, kinds: &[&str], comments: &mut Vec<Node<'tree>>) {
+    for i in 0..node.child_count() {
+        let child = node.child(i).unwrap();
+        if kinds.contains(&child.kind()) {
+            comments.push(child);
+        } else {
+            collect_comments(child, kinds, comments);
+        }
+    }
+}
+
+// Reads the directive at the start of a comment, as in `# plexity:
+// ignore-next`, so that comments merely mentioning one are left alone
+fn directive(comment: &str) -> Option<Directive> {
+    let text = comment.trim_start_matches(|c: char| c.is_whitespace() || "#/*!<-;".contains(c));
+    let word = text.strip_prefix(MARKER)?.split_whitespace().next()?;
+    // Closing delimiters may follow without a space, as in `ignore-end*/`
+    match word.trim_end_matches(['*', '/', '-', '>']) {
+        "ignore-next" => Some(Directive::Next),
+        "ignore-start" => Some(Directive::Start),
+        "ignore-end" => Some(Directive::End),
+        "ignore-file" => Some(Directive::File),
+        _ => None,
+    }
+}
+
+// The construct that starts with the first token after `comment`: the
+// largest node starting there that does not hold a list of statements
+fn next_construct<'tree>(
+    root: Node<'tree>,
+    comment: Node<'tree>,
+    source: &str,
+    language: Language,
+) -> Option<Node<'tree>> {
+    let mut node = comment;
+    // Skip over any further comments between the directive and the code
+    while comment_kinds(language).contains(&node.kind()) {
+        node = next_leaf(root, node)?;
+    }
+
+    let start = node.start_byte();
+    while let Some(parent) = node.parent() {
+        let widens = parent.parent().is_some()
+            && parent.start_byte() == start
+            && !holds_lines(parent, source);
+        if !widens {
+            break;
+        }
+        node = parent;
+    }
+    Some(node)
+}
+
+// The first node starting after `node` ends
+fn next_leaf<'tree>(root: Node<'tree>, node: Node<'tree>) -> Option<Node<'tree>> {
+    let mut current = node;
+    loop {
+        if let Some(sibling) = current.next_sibling() {
+            let mut leaf = sibling;
+            while let Some(child) = leaf.child(0) {
+                if child.start_byte() != leaf.start_byte() {
+                    break;
+                }
+                leaf = child;
+            }
+            return Some(leaf);
+        }
+        current = current.parent().filter(|parent| *parent != root)?;
+    }
+}
+
+// Whether `node` holds several constructs that each start a line, as a
+// block of statements does
+fn holds_lines(node: Node, source: &str) -> bool {
+    node.named_child_count() > 1
+        && (0..node.named_child_count())
+            .filter_map(|i| node.named_child(i))
+            .all(|child| starts_line(child, source))
+}
+
+fn starts_line(node: Node, source: &str) -> bool {
+    let start = node.start_byte();
+    let line_start = source[..start].rfind('\n').map_or(0, |index| index + 1);
+    source[line_start..start].trim().is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_directives() {
+        assert_eq!(directive("# plexity: ignore-next"), Some(Directive::Next));
+        assert_eq!(
+            directive("/* plexity: ignore-start */"),
+            Some(Directive::Start)
+        );
+        assert_eq!(directive("/*plexity:ignore-end*/"), Some(Directive::End));
+        assert_eq!(
+            directive("<!-- plexity: ignore-file -->"),
+            Some(Directive::File)
+        );
+        assert_eq!(directive("// plexity: ignore-everything"), None);
+        assert_eq!(directive("// ignore-next"), None);
+        assert_eq!(directive("/* Use plexity: ignore-next */"), None);
+    }
+}
@@ -0,0 +1,198 @@
+/*  A module for gathering the syntax tree walked while measuring a file,
+so that it can be drawn. Depths are the same as in the scorecard, and the
+tree can be cut down to its named nodes, to a maximum depth or to a range
+of lines, so that large files still give a readable picture.
+
+Every node keeps a link to its nearest ancestor that was also kept, so a
+named node under a hidden anonymous one still hangs from the tree.  */
+
+use crate::report::{NodeRecord, Span};
+use crate::trace::LineRange;
+use std::collections::HashMap;
+use tree_sitter::Node;
+
+// The most characters of source text kept for each leaf
+const TEXT_LENGTH: usize = 32;
+
+/// Which nodes of the tree to keep. Every condition that is set must hold
+/// for a node to be kept.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Cutoff {
+    /// Leave out anonymous nodes, such as punctuation and keywords.
+    pub named_only: bool,
+    pub maximum_depth: Option<usize>,
+    pub lines: Option<LineRange>,
+}
+
+impl Cutoff {
+    fn keeps(&self, node: Node, record: &NodeRecord) -> bool {
+        (node.is_named() || !self.named_only)
+            && self.maximum_depth.is_none_or(|depth| record.depth <= depth)
+            && self
+                .lines
+                .is_none_or(|lines| lines.overlaps(record.span.start.line, record.span.end.line))
+    }
+}
+
+/// A node of the syntax tree, as it is drawn.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TreeNode {
+    pub kind: String,
+    /// The field of its parent that the node fills, such as `condition`.
+    pub field: Option<&'static str>,
+    pub named: bool,
+    pub depth: usize,
+    pub span: Span,
+    /// The source code of a leaf, shortened when it is long.
+    pub text: Option<String>,
+    /// The position of the parent among the kept nodes, unless the node
+    /// hangs from the root.
+    pub parent: Option<usize>,
+}
+
+/// Gathers the nodes visited while traversing a tree, for use as (part
+/// of) the callback to `analyze_with`.
+#[derive(Debug, Default)]
+pub struct Collector {
+    cutoff: Cutoff,
+    nodes: Vec<TreeNode>,
+    // The position of each kept node, by its tree-sitter ID
+    positions: HashMap<usize, usize>,
+}
+
+impl Collector {
+    pub fn new(cutoff: Cutoff) -> Collector {
+        Collector {
+            cutoff,
+            ..Default::default()
+        }
+    }
+
+    /// Keeps the visited `node` when it passes the cutoff.
+    pub fn visit(&mut self, node: Node, record: &NodeRecord, source: &str) {
+        if !self.cutoff.keeps(node, record) {
+            return;
+        }
+        let mut ancestor = node.parent();
+        let parent = loop {
+            match ancestor {
+                Some(current) => match self.positions.get(&current.id()) {
+                    Some(&position) => break Some(position),
+                    None => ancestor = current.parent(),
+                },
+                None => break None,
+            }
+        };
+        let text = (node.child_count() == 0)
+            .then(|| shorten(node.utf8_text(source.as_bytes()).unwrap_or_default()));
+
+        self.positions.insert(node.id(), self.nodes.len());
+        self.nodes.push(TreeNode {
+            kind: record.kind.clone(),
+            field: field_name(node),
+            named: node.is_named(),
+            depth: record.depth,
+            span: record.span,
+            text,
+            parent,
+        });
+    }
+
+    /// The kept nodes, in the order they were visited.
+    pub fn finish(self) -> Vec<TreeNode> {
+        self.nodes
+    }
+}
+
+fn field_name(node: Node) -> Option<&'static str> {
+    let parent = node.parent()?;
+    let index = (0..parent.child_count()).find(|&i| parent.child(i) == Some(node))?;
+    parent.field_name_for_child(index as u32)
+}
+
+// Leaves can be long strings or comments, so only their start is kept
+fn shorten(text: &str) -> String {
+    let first_line = text.lines().next().unwrap_or_default();
+    let mut short: String = first_line.chars().take(TEXT_LENGTH).collect();
+    if short.len() < text.len() {
+        short.push_str("...");
+    }
+    short
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::language::Language;
+
+    fn collect(source: &str, cutoff: Cutoff) -> Vec<TreeNode> {
+        let mut collector = Collector::new(cutoff);
+        crate::analyze_with(source, Language::Python, |node, record| {
+            collector.visit(node, record, source)
+        })
+        .unwrap();
+        collector.finish()
+    }
+
+    #[test]
+    fn test_fields_parents_and_text() {
+        let nodes = collect("if a:\n    b = 1\n", Cutoff::default());
+        assert_eq!(nodes[0].kind, "if_statement");
+        assert_eq!(nodes[0].parent, None);
+        assert_eq!(nodes[0].depth, 0);
+        let condition = nodes.iter().find(|node| node.kind == "identifier").unwrap();
+        assert_eq!(condition.field, Some("condition"));
+        assert_eq!(condition.text.as_deref(), Some("a"));
+        assert_eq!(condition.parent, Some(0));
+        assert!(nodes.iter().any(|node| !node.named && node.kind == ":"));
+    }
+
+    #[test]
+    fn test_cutoffs() {
+        let source = "if a:\n    b = 1\nc = (2)\n";
+        let named = collect(
+            source,
+            Cutoff {
+                named_only: true,
+                ..Default::default()
+            },
+        );
+        assert!(named.iter().all(|node| node.named));
+        assert!(named
+            .iter()
+            .enumerate()
+            .all(|(i, node)| node.parent.is_none_or(|parent| parent < i)));
+        let integer = named
+            .iter()
+            .rposition(|node| node.kind == "integer")
+            .unwrap();
+        let parenthesized = named[integer].parent.unwrap();
+        assert_eq!(named[parenthesized].kind, "parenthesized_expression");
+
+        let shallow = collect(
+            source,
+            Cutoff {
+                maximum_depth: Some(1),
+                ..Default::default()
+            },
+        );
+        assert!(shallow.iter().all(|node| node.depth <= 1));
+
+        let lines = collect(
+            source,
+            Cutoff {
+                lines: Some("3".parse().unwrap()),
+                ..Default::default()
+            },
+        );
+        assert!(lines.iter().all(|node| node.span.start.line == 3));
+        assert_eq!(lines[0].parent, None);
+    }
+
+    #[test]
+    fn test_shorten() {
+        assert_eq!(shorten("abc"), "abc");
+        assert_eq!(shorten("\"\"\"first\nsecond\"\"\""), "\"\"\"first...");
+        assert_eq!(shorten(&"x".repeat(40)), format!("{}...", "x".repeat(32)));
+    }
+}
@@ -0,0 +1,177 @@
+/*  A module for tracing the individual nodes visited while traversing a
+syntax tree, filtered down to the depths, kinds and lines of interest.  */
+
+use crate::report::NodeRecord;
+use serde::Serialize;
+use std::fmt;
+use std::str::FromStr;
+
+/// An inclusive range of one-based line numbers, written as `10-20`,
+/// `10-` (from line 10 onwards) or `10` (line 10 only).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineRange {
+    pub start: usize,
+    pub end: Option<usize>,
+}
+
+impl LineRange {
+    /// Whether any of the lines from `first` to `last` fall in the range.
+    pub fn overlaps(&self, first: usize, last: usize) -> bool {
+        last >= self.start && self.end.is_none_or(|end| first <= end)
+    }
+}
+
+impl FromStr for LineRange {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<LineRange, String> {
+        let number = |text: &str| {
+            text.trim()
+                .parse::<usize>()
+                .ok()
+                .filter(|line| *line > 0)
+                .ok_or_else(|| format!("\"{text}\" is not a line number"))
+        };
+        let range = match text.split_once('-') {
+            Some((start, "")) => LineRange {
+                start: number(start)?,
+                end: None,
+            },
+            Some((start, end)) => LineRange {
+                start: number(start)?,
+                end: Some(number(end)?),
+            },
+            None => LineRange {
+                start: number(text)?,
+                end: Some(number(text)?),
+            },
+        };
+        if range.end.is_some_and(|end| end < range.start) {
+            return Err(format!("the line range \"{text}\" ends before it starts"));
+        }
+        Ok(range)
+    }
+}
+
+impl fmt::Display for LineRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.end {
+            Some(end) => write!(f, "{}-{}", self.start, end),
+            None => write!(f, "{}-", self.start),
+        }
+    }
+}
+
+/// Picks out the nodes to include in a trace. Every condition that is set
+/// must hold for a node to be included.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Filter {
+    pub minimum_depth: Option<usize>,
+    /// Node kinds, such as `if_statement`; any kind matches when empty.
+    pub kinds: Vec<String>,
+    pub lines: Option<LineRange>,
+}
+
+impl Filter {
+    /// Whether the node described by `record` passes the filter.
+    pub fn matches(&self, record: &NodeRecord) -> bool {
+        self.minimum_depth.is_none_or(|depth| record.depth >= depth)
+            && (self.kinds.is_empty() || self.kinds.contains(&record.kind))
+            && self
+                .lines
+                .is_none_or(|lines| lines.overlaps(record.span.start.line, record.span.end.line))
+    }
+}
+
+#[derive(Serialize)]
+struct TraceLine<'a> {
+    file: &'a str,
+    #[serde(flatten)]
+    record: &'a NodeRecord,
+}
+
+/// Renders a traced node as a single line of text. `maximum_depth` is the
+/// deepest node seen so far in the traversal.
+pub fn text_line(record: &NodeRecord, maximum_depth: usize) -> String {
+    format!(
+        "#{} | depth:{}/{} | beg:{}:{} end:{}:{} | kind: {} | cyclo?: {}",
+        record.index,
+        record.depth,
+        maximum_depth,
+        record.span.start.line,
+        record.span.start.column,
+        record.span.end.line,
+        record.span.end.column,
+        record.kind,
+        record.cyclomatic
+    )
+}
+
+/// Renders a traced node from the file at `path` as a line of JSON, so
+/// that a whole trace can be read as JSON Lines.
+pub fn json_line(path: &str, record: &NodeRecord) -> String {
+    serde_json::to_string(&TraceLine { file: path, record }).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::{Position, Span};
+
+    fn record(depth: usize, kind: &str, first: usize, last: usize) -> NodeRecord {
+        NodeRecord {
+            index: 7,
+            depth,
+            kind: kind.to_string(),
+            span: Span {
+                start: Position {
+                    line: first,
+                    column: 1,
+                },
+                end: Position {
+                    line: last,
+                    column: 4,
+                },
+            },
+            cyclomatic: false,
+        }
+    }
+
+    #[test]
+    fn test_line_range_parsing() {
+        let range: LineRange = "10-20".parse().unwrap();
+        assert_eq!((range.start, range.end), (10, Some(20)));
+        assert_eq!("5-".parse::<LineRange>().unwrap().end, None);
+        assert_eq!("5".parse::<LineRange>().unwrap().end, Some(5));
+        assert!("0-4".parse::<LineRange>().is_err());
+        assert!("9-3".parse::<LineRange>().is_err());
+        assert!("a-b".parse::<LineRange>().is_err());
+    }
+
+    #[test]
+    fn test_filter_conditions() {
+        let filter = Filter {
+            minimum_depth: Some(2),
+            kinds: vec!["call".to_string()],
+            lines: Some("3-4".parse().unwrap()),
+        };
+        assert!(filter.matches(&record(2, "call", 1, 3)));
+        assert!(!filter.matches(&record(1, "call", 3, 3)));
+        assert!(!filter.matches(&record(2, "identifier", 3, 3)));
+        assert!(!filter.matches(&record(2, "call", 5, 6)));
+        assert!(Filter::default().matches(&record(0, "module", 1, 1)));
+    }
+
+    #[test]
+    fn test_trace_lines() {
+        let node = record(3, "call", 2, 2);
+        assert_eq!(
+            text_line(&node, 4),
+            "#7 | depth:3/4 | beg:2:1 end:2:4 | kind: call | cyclo?: false"
+        );
+        let value: serde_json::Value = serde_json::from_str(&json_line("a.py", &node)).unwrap();
+        assert_eq!(value["file"], "a.py");
+        assert_eq!(value["kind"], "call");
+        assert_eq!(value["span"]["end"]["column"], 4);
+    }
+}
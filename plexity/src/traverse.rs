@@ -0,0 +1,263 @@
+/*  A module for walking an abstract syntax tree and evaluating
+the resultant tree's complexity.  */
+
+use crate::cognitive;
+use crate::cyclomatic;
+use crate::error::Error;
+use crate::functions;
+use crate::halstead;
+use crate::language::Language;
+use crate::maintainability;
+use crate::report::{FunctionReport, Metrics, NodeRecord, Report};
+use crate::suppress::Suppressions;
+use tree_sitter::{Node, Parser, Tree};
+
+/// Parses `source_code` and gathers the metrics for the resulting tree.
+pub fn traverse_tree(
+    source_code: &str,
+    parser: Parser,
+    language: Language,
+) -> Result<Report, Error> {
+    traverse_tree_with(source_code, parser, language, |_, _| {})
+}
+
+/// Like `traverse_tree`, but calls `on_node` for every node as it is visited.
+pub fn traverse_tree_with<F>(
+    source_code: &str,
+    mut parser: Parser,
+    language: Language,
+    mut on_node: F,
+) -> Result<Report, Error>
+where
+    F: FnMut(Node, &NodeRecord),
+{
+    let parse_tree: Tree = parser.parse(source_code, None).ok_or(Error::Parse)?;
+    let root_node: Node = parse_tree.root_node();
+    let suppressions = Suppressions::find(root_node, source_code, language);
+
+    // Unpack nodes recursively, starting with the root node
+    let metrics = measure_node(
+        root_node,
+        source_code,
+        language,
+        None,
+        &suppressions,
+        &mut on_node,
+    );
+
+    // Then measure each function that is not suppressed on its own
+    let functions = functions::discover(root_node, source_code, language)
+        .into_iter()
+        .filter(|function| !suppressions.covers(function.node))
+        .map(|function| FunctionReport {
+            span: function.node.range().into(),
+            metrics: measure_node(
+                function.node,
+                source_code,
+                language,
+                Some(&function.name),
+                &suppressions,
+                &mut |_, _| {},
+            ),
+            name: function.name,
+        })
+        .collect();
+
+    Ok(Report { metrics, functions })
+}
+
+/// Gathers the metrics for the subtree below `node`, treating `node` as
+/// the root so that its children have a depth of zero. When `node` is a
+/// function, `function` holds its name so that recursion can be detected.
+/// Subtrees covered by `suppressions` are skipped.
+pub fn measure_node<F>(
+    node: Node,
+    source_code: &str,
+    language: Language,
+    function: Option<&str>,
+    suppressions: &Suppressions,
+    on_node: &mut F,
+) -> Metrics
+where
+    F: FnMut(Node, &NodeRecord),
+{
+    let mut metrics = Metrics::default();
+    let mut cyclomatic_count: usize = 0;
+    unpack_node(
+        node,
+        0,
+        language,
+        suppressions,
+        &mut metrics,
+        &mut cyclomatic_count,
+        on_node,
+    );
+
+    if metrics.node_count > 0 {
+        metrics.average_depth = metrics.combined_depth as f64 / metrics.node_count as f64;
+    }
+    if cyclomatic::supported(language) {
+        metrics.cyclomatic_complexity = Some(cyclomatic_count + 1);
+    }
+    metrics.cognitive_complexity =
+        cognitive::cognitive_complexity(node, source_code, language, function, suppressions);
+    metrics.halstead = halstead::halstead(node, source_code, language, suppressions);
+    metrics.source_lines = maintainability::source_lines(node, suppressions);
+    metrics.maintainability = maintainability::maintainability(&metrics);
+    metrics
+}
+
+fn unpack_node<F>(
+    node: Node,
+    current_depth: usize,
+    language: Language,
+    suppressions: &Suppressions,
+    metrics: &mut Metrics,
+    cyclomatic_count: &mut usize,
+    on_node: &mut F,
+) where
+    F: FnMut(Node, &NodeRecord),
+{
+    for i in 0..node.child_count() {
+        let child = node.child(i).unwrap();
+        if suppressions.covers(child) {
+            metrics.suppressed_nodes += 1 + descendant_count(child);
+            continue;
+        }
+        metrics.node_count += 1;
+
+        if current_depth > metrics.maximum_depth || metrics.deepest_node.is_none() {
+            metrics.maximum_depth = current_depth;
+            metrics.deepest_node = Some(child.range().into());
+        };
+
+        let is_cyclomatic = cyclomatic::is_decision_point(child, language);
+
+        if is_cyclomatic {
+            *cyclomatic_count += 1;
+        }
+
+        let record = NodeRecord {
+            index: metrics.node_count,
+            depth: current_depth,
+            kind: child.kind().to_string(),
+            span: child.range().into(),
+            cyclomatic: is_cyclomatic,
+        };
+        on_node(child, &record);
+
+        metrics.combined_depth += current_depth;
+
+        unpack_node(
+            child,
+            current_depth + 1,
+            language,
+            suppressions,
+            metrics,
+            cyclomatic_count,
+            on_node,
+        );
+    }
+}
+
+fn descendant_count(node: Node) -> usize {
+    (0..node.child_count())
+        .map(|i| 1 + descendant_count(node.child(i).unwrap()))
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::language::select_parser;
+
+    #[test]
+    fn test_traverse_tree_hello_world() {
+        let parser = select_parser(Language::Python).unwrap();
+        let report = traverse_tree("print(\"Hello world\")", parser, Language::Python).unwrap();
+        assert_eq!(report.metrics.node_count, 10);
+        assert_eq!(report.metrics.maximum_depth, 4);
+        let deepest = report.metrics.deepest_node.unwrap();
+        assert_eq!((deepest.start.line, deepest.start.column), (1, 7));
+        assert_eq!(report.metrics.cyclomatic_complexity, Some(1));
+    }
+
+    #[test]
+    fn test_traverse_tree_cyclomatic() {
+        let source_code = std::fs::read_to_string("data/python/cyclomatic.py").unwrap();
+        let parser = select_parser(Language::Python).unwrap();
+        let report = traverse_tree(&source_code, parser, Language::Python).unwrap();
+        assert_eq!(report.metrics.cyclomatic_complexity, Some(15));
+    }
+
+    #[test]
+    fn test_traverse_tree_with_visits_every_node() {
+        let parser = select_parser(Language::Python).unwrap();
+        let mut records = Vec::new();
+        let report = traverse_tree_with("x = 1", parser, Language::Python, |_, record| {
+            records.push(record.clone())
+        })
+        .unwrap();
+        assert_eq!(records.len(), report.metrics.node_count);
+        assert_eq!(records[0].kind, "expression_statement");
+        assert_eq!(records[0].span.start.line, 1);
+    }
+
+    #[test]
+    fn test_traverse_tree_measures_functions() {
+        let source_code = std::fs::read_to_string("data/fibonacci/fib-python.py").unwrap();
+        let parser = select_parser(Language::Python).unwrap();
+        let report = traverse_tree(&source_code, parser, Language::Python).unwrap();
+        assert!(!report.functions.is_empty());
+        for function in &report.functions {
+            assert!(function.metrics.node_count < report.metrics.node_count);
+            assert!(function.metrics.cyclomatic_complexity.unwrap() >= 1);
+        }
+    }
+
+    #[test]
+    fn test_traverse_tree_skips_suppressed_code() {
+        let plain = "def f(a):\n    if a:\n        return [[[a]]]\n    return 1\n";
+        let suppressed = "def f(a):\n    # plexity: ignore-next\n    if a:\n        return [[[a]]]\n    return 1\n";
+        let measure = |source| {
+            let parser = select_parser(Language::Python).unwrap();
+            traverse_tree(source, parser, Language::Python).unwrap()
+        };
+        let (plain, suppressed) = (measure(plain), measure(suppressed));
+        assert!(suppressed.metrics.maximum_depth < plain.metrics.maximum_depth);
+        assert_eq!(suppressed.metrics.cyclomatic_complexity, Some(1));
+        assert_eq!(
+            suppressed.functions[0].metrics.cognitive_complexity,
+            Some(0)
+        );
+        // Every node is either counted or suppressed, besides the comment
+        assert_eq!(
+            suppressed.metrics.node_count + suppressed.metrics.suppressed_nodes,
+            plain.metrics.node_count + 1
+        );
+    }
+
+    #[test]
+    fn test_traverse_tree_suppressed_regions_and_files() {
+        let source = "fn a() {}\n// plexity: ignore-start\nfn b() { if x { y } }\nfn c() {}\n// plexity: ignore-end\nfn d() {}\n";
+        let parser = select_parser(Language::Rust).unwrap();
+        let report = traverse_tree(source, parser, Language::Rust).unwrap();
+        let names: Vec<&str> = report.functions.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, vec!["a", "d"]);
+
+        let source = "# plexity: ignore-file\nx = [[[1]]]\n";
+        let parser = select_parser(Language::Python).unwrap();
+        let report = traverse_tree(source, parser, Language::Python).unwrap();
+        assert_eq!(report.metrics.node_count, 0);
+        assert!(report.metrics.suppressed_nodes > 0);
+        assert_eq!(report.metrics.halstead.as_ref().unwrap().length, 0);
+        assert_eq!(report.metrics.source_lines, 0);
+    }
+
+    #[test]
+    fn test_traverse_tree_json_has_no_cyclomatic() {
+        let parser = select_parser(Language::Json).unwrap();
+        let report = traverse_tree("{\"a\": 1}", parser, Language::Json).unwrap();
+        assert_eq!(report.metrics.cyclomatic_complexity, None);
+    }
+}
@@ -0,0 +1,275 @@
+/*  A module for expanding the paths given on the command line into the
+list of source files to analyse.  */
+
+use crate::detect::{self, Mappings};
+use crate::language::Language;
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use ignore::WalkBuilder;
+use std::fs;
+use std::io;
+use std::path::{self, Path, PathBuf};
+
+/// The name of the file listing paths that plexity should leave out, in
+/// the same format as a `.gitignore` file.
+pub const IGNORE_FILE: &str = ".plexityignore";
+
+// How much of each end of a file is read to tell what it holds
+const SAMPLE_BYTES: u64 = 8192;
+
+// How many lines at the start of a file are searched for generated markers
+const GENERATED_LINES: usize = 10;
+
+/// A set of glob patterns, such as `src/**` or `**/*.min.js`, that are
+/// matched against paths relative to a root directory.
+#[derive(Debug, Clone)]
+pub struct PathGlobs {
+    root: PathBuf,
+    set: GlobSet,
+}
+
+impl PathGlobs {
+    /// Compiles `patterns`, which are relative to `root`. A `*` does not
+    /// match across directories, while a `**` does.
+    pub fn new(root: &Path, patterns: &[String]) -> Result<PathGlobs, globset::Error> {
+        let mut builder = GlobSetBuilder::new();
+        for pattern in patterns {
+            builder.add(GlobBuilder::new(pattern).literal_separator(true).build()?);
+        }
+        Ok(PathGlobs {
+            root: path::absolute(root).unwrap_or_else(|_| root.to_path_buf()),
+            set: builder.build()?,
+        })
+    }
+
+    /// Whether `path` matches any of the patterns. Paths outside of the
+    /// root are matched as they were given.
+    pub fn is_match(&self, path: &Path) -> bool {
+        let absolute = path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+        match absolute.strip_prefix(&self.root) {
+            Ok(relative) => self.set.is_match(relative),
+            Err(_) => self.set.is_match(path),
+        }
+    }
+}
+
+/// Decides which of the files found in directories are analysed.
+#[derive(Debug, Clone, Default)]
+pub struct Selection {
+    /// Only files written in this language, when it is given.
+    pub language: Option<Language>,
+    /// Custom mappings used to detect the language of each file.
+    pub mappings: Mappings,
+    /// Only files matching these globs, when they are given.
+    pub include: Option<PathGlobs>,
+    /// Files and directories to leave out, when they match any of these.
+    pub exclude: Vec<PathGlobs>,
+    /// Whether to also walk hidden directories and the paths listed in
+    /// `.gitignore`, `.ignore` and `.plexityignore` files.
+    pub include_ignored: bool,
+}
+
+impl Selection {
+    fn included(&self, path: &Path) -> bool {
+        self.include
+            .as_ref()
+            .is_none_or(|globs| globs.is_match(path))
+            && !excluded(&self.exclude, path)
+    }
+
+    // Whether a file found in a directory is source code in the selected
+    // language. Binary and generated files are skipped, as are files that
+    // cannot be read
+    fn accepts(&self, path: &Path) -> bool {
+        let sample = match detect::read_sample(path, SAMPLE_BYTES) {
+            Ok(sample) if !is_binary(&sample) => sample,
+            _ => return false,
+        };
+        let sample = String::from_utf8_lossy(&sample);
+        if is_generated(&sample) {
+            return false;
+        }
+        match self.mappings.detect(path, &sample) {
+            Some(detected) => self.language.is_none_or(|language| language == detected),
+            None => false,
+        }
+    }
+}
+
+fn excluded(exclude: &[PathGlobs], path: &Path) -> bool {
+    exclude.iter().any(|globs| globs.is_match(path))
+}
+
+/// Expands `paths` into the files to analyse. Files are kept as they were
+/// given, while directories are searched recursively for files that are
+/// written in the selected language (or in any recognised language) and
+/// that the selection includes. Binary and generated files are skipped,
+/// as are hidden directories (such as `.git`) and paths listed in ignore
+/// files unless the selection includes ignored paths.
+pub fn collect_files(paths: &[PathBuf], selection: &Selection) -> io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for path in paths {
+        if fs::metadata(path)?.is_dir() {
+            walk_directory(path, selection, &mut files)?;
+        } else {
+            files.push(path.clone());
+        }
+    }
+    Ok(files)
+}
+
+fn walk_directory(
+    directory: &Path,
+    selection: &Selection,
+    files: &mut Vec<PathBuf>,
+) -> io::Result<()> {
+    let exclude = selection.exclude.clone();
+    let skip_hidden = !selection.include_ignored;
+    let mut walker = WalkBuilder::new(directory);
+    walker
+        .standard_filters(!selection.include_ignored)
+        .hidden(false)
+        .require_git(false)
+        .sort_by_file_name(|a, b| a.cmp(b))
+        .filter_entry(move |entry| {
+            let is_dir = entry
+                .file_type()
+                .is_some_and(|file_type| file_type.is_dir());
+            let hidden = skip_hidden && entry.file_name().to_string_lossy().starts_with('.');
+            // The directory that was asked for is always walked
+            entry.depth() == 0 || !is_dir || !(hidden || excluded(&exclude, entry.path()))
+        });
+    if !selection.include_ignored {
+        walker.add_custom_ignore_filename(IGNORE_FILE);
+    }
+
+    for entry in walker.build() {
+        let entry = entry.map_err(io::Error::other)?;
+        let is_file = entry
+            .file_type()
+            .is_some_and(|file_type| file_type.is_file());
+        if is_file && selection.included(entry.path()) && selection.accepts(entry.path()) {
+            files.push(entry.into_path());
+        }
+    }
+    Ok(())
+}
+
+// Text files hold no NUL bytes, which is also how Git tells them apart
+fn is_binary(sample: &[u8]) -> bool {
+    sample.contains(&0)
+}
+
+// Generated files carry a marker such as `@generated` or Go's `Code
+// generated by ... DO NOT EDIT.` near the top
+fn is_generated(head: &str) -> bool {
+    head.lines().take(GENERATED_LINES).any(|line| {
+        line.contains("@generated")
+            || (line.contains("Code generated") && line.contains("DO NOT EDIT"))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A fresh directory for one test, which cannot clash with another run
+    fn temp_dir(test: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("plexity-walk-{test}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        root
+    }
+
+    fn language(language: Language) -> Selection {
+        Selection {
+            language: Some(language),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_collect_files_walks_directories() {
+        let files = collect_files(&[PathBuf::from("data")], &language(Language::Python)).unwrap();
+        assert!(files.contains(&PathBuf::from("data/python/hello-world.py")));
+        assert!(files.contains(&PathBuf::from("data/fibonacci/fib-python.py")));
+        assert!(files.iter().all(|file| file.extension().unwrap() == "py"));
+    }
+
+    #[test]
+    fn test_collect_files_detects_every_language() {
+        let files = collect_files(&[PathBuf::from("data")], &Selection::default()).unwrap();
+        assert!(files.contains(&PathBuf::from("data/Dockerfile")));
+        assert!(files.contains(&PathBuf::from("data/fibonacci/fib-c.c")));
+        assert!(files.contains(&PathBuf::from("data/sample.json")));
+    }
+
+    #[test]
+    fn test_collect_files_keeps_explicit_files() {
+        let paths = [
+            PathBuf::from("data/sample.json"),
+            PathBuf::from("data/rust"),
+        ];
+        let files = collect_files(&paths, &language(Language::Python)).unwrap();
+        assert_eq!(files, vec![PathBuf::from("data/sample.json")]);
+    }
+
+    #[test]
+    fn test_collect_files_missing_path() {
+        assert!(collect_files(&[PathBuf::from("no-such-path")], &Selection::default()).is_err());
+    }
+
+    #[test]
+    fn test_collect_files_include_and_exclude() {
+        let root = Path::new(".");
+        let selection = Selection {
+            include: Some(PathGlobs::new(root, &["data/**/*.py".to_string()]).unwrap()),
+            exclude: vec![PathGlobs::new(root, &["data/fibonacci".to_string()]).unwrap()],
+            ..Default::default()
+        };
+        let files = collect_files(&[PathBuf::from("data")], &selection).unwrap();
+        assert!(files.contains(&PathBuf::from("data/python/hello-world.py")));
+        assert!(!files.contains(&PathBuf::from("data/fibonacci/fib-python.py")));
+        assert!(!files.contains(&PathBuf::from("data/sample.json")));
+    }
+
+    #[test]
+    fn test_collect_files_skips_ignored_binary_and_generated_files() {
+        let root = temp_dir("skips-ignored");
+        for directory in ["src", "target", "vendor", "node_modules", ".cache"] {
+            fs::create_dir_all(root.join(directory)).unwrap();
+            fs::write(root.join(directory).join("a.py"), "pass\n").unwrap();
+        }
+        fs::write(root.join(".gitignore"), "target/\n").unwrap();
+        fs::write(root.join(".ignore"), "node_modules/\n").unwrap();
+        fs::write(root.join(IGNORE_FILE), "vendor/\n").unwrap();
+        fs::write(root.join("src/b.py"), "# @generated by a tool\npass\n").unwrap();
+        fs::write(root.join("src/c.py"), b"pass\n\0\x01").unwrap();
+
+        let files = collect_files(std::slice::from_ref(&root), &Selection::default()).unwrap();
+        assert_eq!(files, vec![root.join("src/a.py")]);
+
+        let selection = Selection {
+            include_ignored: true,
+            ..Default::default()
+        };
+        let files = collect_files(std::slice::from_ref(&root), &selection).unwrap();
+        assert_eq!(files.len(), 5);
+        assert!(files.contains(&root.join(".cache/a.py")));
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_generated_markers() {
+        assert!(is_generated(
+            "// Code generated by protoc-gen-go. DO NOT EDIT.\n"
+        ));
+        assert!(is_generated("/**\n * This file is @generated\n */\n"));
+        assert!(!is_generated("def generated():\n    pass\n"));
+    }
+
+    #[test]
+    fn test_path_globs_star_stays_in_directory() {
+        let globs = PathGlobs::new(Path::new("/repo"), &["src/*.rs".to_string()]).unwrap();
+        assert!(globs.is_match(Path::new("/repo/src/main.rs")));
+        assert!(!globs.is_match(Path::new("/repo/src/output/json.rs")));
+    }
+}